@@ -1,99 +1,370 @@
+use std::ops::Range;
+
+use crate::basics::ContactDamage;
+use crate::damage::{DeathEvent, Resistances};
 use crate::magnet::MagnetAttractable;
+use crate::ron_asset::{texture_handle, RonAsset};
 
 use super::*;
+use bevy::asset::{AssetPath, LoadContext, LoadedAsset};
 use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use rand::Rng;
+use serde::Deserialize;
+
+/// Kind of asteroid, loaded from a `.asteroid.ron` file in the `asteroids` folder.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "7c4b5e0e-3a0f-4f8e-b1a8-5b0c9e6f2d41"]
+pub struct AsteroidArchetype {
+    /// How likely the spawner picks this archetype relative to the others.
+    pub weight: f32,
+    /// First wave the director sends this archetype in.
+    #[serde(default)]
+    pub first_wave: u32,
+    /// Shape of the asteroid before scaling, shards share it too.
+    pub collider: Collider,
+    /// Scale of the sprite.
+    pub scale: f32,
+    /// Mass for bouncing off other asteroids and ships.
+    pub mass: f32,
+    /// Hitpoints at full health, smaller tiers should get fewer.
+    pub hitpoints: u32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    /// Damage dealt to anything ramming into the asteroid faster than `impacts.damage_speed`.
+    pub contact_damage: u32,
+    pub speed: Range<f32>,
+    /// Angular velocity in radians per second, negative spins clockwise.
+    #[serde(default)]
+    pub spin: Range<f32>,
+    pub sprite: AtlasSprite,
+    /// Sprite frames from undamaged to destroyed, picked by the portion of hitpoints lost.
+    pub damage_frames: Vec<usize>,
+    /// Smaller asteroids it breaks into when destroyed.
+    #[serde(default)]
+    pub split: Option<Split>,
+    /// Number of shards dropped when destroyed, only by asteroids that don't split.
+    #[serde(default)]
+    pub shards: u32,
+    /// Score for destroying the asteroid.
+    pub score: u32,
+}
+
+#[derive(Deserialize)]
+pub struct Split {
+    /// Path of the archetype of the pieces.
+    pub into: String,
+    pub pieces: u32,
+    /// Speed the pieces fly apart with, on top of the velocity of the destroyed asteroid.
+    pub spread: f32,
+}
+
+#[derive(Deserialize)]
+pub struct AtlasSprite {
+    pub texture: String,
+    pub tile_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    #[serde(skip)]
+    pub atlas: Handle<TextureAtlas>,
+}
+
+impl RonAsset for AsteroidArchetype {
+    const EXTENSIONS: &'static [&'static str] = &["asteroid.ron"];
+
+    fn load_textures(&mut self, load_context: &mut LoadContext) -> Vec<AssetPath<'static>> {
+        self.sprite.load_atlas(load_context)
+    }
+}
+
+impl AtlasSprite {
+    /// Cuts the texture into the atlas, added as the `atlas` sub-asset, and returns the texture path.
+    pub fn load_atlas(&mut self, load_context: &mut LoadContext) -> Vec<AssetPath<'static>> {
+        let mut textures = Vec::new();
+        let texture = texture_handle(load_context, &self.texture, &mut textures);
+        let atlas = TextureAtlas::from_grid(texture, self.tile_size, self.columns, self.rows);
+        self.atlas = load_context.set_labeled_asset("atlas", LoadedAsset::new(atlas));
+        textures
+    }
+}
+
+impl AsteroidArchetype {
+    pub fn random_spin(&self, rng: &mut impl Rng) -> f32 {
+        self.spin.start + (self.spin.end - self.spin.start) * rng.gen::<f32>()
+    }
+
+    fn frame(&self, hp: &Hitpoints) -> usize {
+        let last = match self.damage_frames.len() {
+            0 => return 0,
+            len => len - 1,
+        };
+
+        let lost = 1. - hp.fraction();
+        self.damage_frames[((lost * last as f32).round() as usize).min(last)]
+    }
+}
+
+/// All the asteroid archetypes, sorted by path so the spawner picks them deterministically.
+pub struct AsteroidArchetypes(Vec<Handle<AsteroidArchetype>>);
+
+impl FromWorld for AsteroidArchetypes {
+    fn from_world(world: &mut World) -> Self {
+        AsteroidArchetypes(LoadingAssets::load_folder(world, "asteroids"))
+    }
+}
+
+impl AsteroidArchetypes {
+    /// The archetypes already loaded, in path order.
+    pub fn loaded<'a>(
+        &'a self,
+        definitions: &'a Assets<AsteroidArchetype>,
+    ) -> Vec<(&'a Handle<AsteroidArchetype>, &'a AsteroidArchetype)> {
+        self.0
+            .iter()
+            .filter_map(|handle| Some((handle, definitions.get(handle)?)))
+            .collect()
+    }
+}
 
 #[derive(Component)]
-pub struct Asteroid;
+pub struct Asteroid {
+    pub archetype: Handle<AsteroidArchetype>,
+}
 
 #[derive(Component)]
-pub struct Shard;
-
-pub fn spawn_asteroids(
-    mut commands: Commands,
-    ship: Res<PlayerSpaceship>,
-    ships: Query<(Entity, &Transform)>,
-    asteroids: Query<(), With<Asteroid>>,
-    materials: Res<GameMaterials>,
+pub struct Shard {
+    pub archetype: Handle<AsteroidArchetype>,
+}
+
+/// Spawns an asteroid of `archetype` on a ring around the ship, flying straight at it.
+pub fn spawn_incoming_asteroid(
+    commands: &mut Commands,
+    rng: &mut impl Rng,
+    handle: &Handle<AsteroidArchetype>,
+    archetype: &AsteroidArchetype,
+    ship: (Entity, &Transform),
+    speed_multiplier: f32,
+) -> Entity {
+    let (ship, ship_transform) = ship;
+    let mut position = around(rng, ship_transform.translation, 1000.);
+
+    let speed =
+        archetype.speed.start + (archetype.speed.end - archetype.speed.start) * rng.gen::<f32>();
+    let direction = (ship_transform.translation - position).normalize() * speed * speed_multiplier;
+
+    position.z += 0.5;
+
+    let spin = archetype.random_spin(rng);
+
+    spawn_asteroid(
+        commands,
+        handle,
+        archetype,
+        Transform::from_translation(position),
+        direction,
+        spin,
+        Some(ship),
+    )
+}
+
+/// Spawns an asteroid of `archetype`, despawned once it gets too far from `anchor`.
+///
+/// Asteroids without an anchor have to be cleaned up some other way. The scale of `transform` is replaced by the one of the archetype.
+pub fn spawn_asteroid(
+    commands: &mut Commands,
+    handle: &Handle<AsteroidArchetype>,
+    archetype: &AsteroidArchetype,
+    transform: Transform,
+    velocity: Vec3,
+    spin: f32,
+    anchor: Option<Entity>,
+) -> Entity {
+    let mut asteroid = commands.spawn_bundle(SpriteSheetBundle {
+        texture_atlas: archetype.sprite.atlas.clone(),
+        transform: Transform {
+            scale: Vec3::splat(archetype.scale),
+            ..transform
+        },
+        ..default()
+    });
+
+    asteroid
+        .insert(CleanupAfterGame)
+        .insert(Asteroid {
+            archetype: handle.clone(),
+        })
+        .insert(Hitpoints::new(archetype.hitpoints))
+        .insert(archetype.resistances)
+        .insert(Velocity::from(velocity))
+        .insert(Rotation::from(spin))
+        .insert(Mass(archetype.mass))
+        .insert(ContactDamage(archetype.contact_damage))
+        .insert(archetype.collider.clone())
+        .insert(HitableByLaser::default());
+
+    if let Some(anchor) = anchor {
+        asteroid.insert(MaximumDistanceFrom {
+            anchor,
+            distance: 1200.0,
+        });
+    }
+
+    asteroid.id()
+}
+
+/// Spawns a shard broken off an asteroid of `archetype`.
+pub fn spawn_shard(
+    commands: &mut Commands,
+    handle: &Handle<AsteroidArchetype>,
+    archetype: &AsteroidArchetype,
+    transform: Transform,
+    velocity: Vec3,
+    spin: f32,
+    lifetime: Lifetime,
+) {
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: archetype.sprite.atlas.clone(),
+            transform,
+            ..default()
+        })
+        .insert(CleanupAfterGame)
+        .insert(Shard {
+            archetype: handle.clone(),
+        })
+        .insert(Velocity::from(velocity))
+        .insert(Rotation::from(spin))
+        .insert(archetype.collider.clone())
+        .insert(lifetime)
+        .insert(MagnetAttractable);
+}
+
+pub fn asteroid_damage(
+    mut asteroids: Query<(&Asteroid, &Hitpoints, &mut TextureAtlasSprite), Changed<Hitpoints>>,
+    definitions: Res<Assets<AsteroidArchetype>>,
 ) {
-    if asteroids.iter().count() < 5 {
-        if let Ok((entity, spaceship)) = ships.get(ship.0) {
-            let mut position = around(spaceship.translation, 1000.);
-
-            let direction = (spaceship.translation - position).normalize() * 100.0;
-
-            position.z += 0.5;
-
-            let _rot = thread_rng().gen_range(-1.5..1.5);
-
-            commands
-                .spawn_bundle(SpriteSheetBundle {
-                    texture_atlas: materials.asteroid.clone(),
-                    transform: Transform::from_translation(position),
-                    ..default()
-                })
-                .insert(CleanupAfterGame)
-                .insert(Asteroid)
-                .insert(Hitpoints(3))
-                .insert(Velocity::from(direction))
-                // .with(Rotation::from(rot))
-                .insert(Collider(Vec2::new(16., 16.)))
-                .insert(HitableByLaser {
-                    damage_tick: Timer::from_seconds(0.15, true),
-                })
-                .insert(MaximumDistanceFrom {
-                    anchor: entity,
-                    distance: 1200.0,
-                });
+    for (asteroid, hp, mut sprite) in asteroids.iter_mut() {
+        if let Some(archetype) = definitions.get(&asteroid.archetype) {
+            sprite.index = archetype.frame(hp);
         }
     }
 }
 
-pub fn asteroid_damage(
+/// Splits destroyed asteroids into smaller ones or shards and credits their killer.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn asteroid_destroyed(
     mut cmd: Commands,
-    mut asteroids: Query<
-        (Entity, &Hitpoints, &mut TextureAtlasSprite, &Transform),
-        With<Asteroid>, // this makes sure asteroid is not already destoyed
-    >,
-    materials: Res<GameMaterials>,
+    mut deaths: EventReader<DeathEvent>,
+    asteroids: Query<(
+        &Asteroid,
+        &Transform,
+        &Velocity,
+        Option<&MaximumDistanceFrom>,
+        Option<&Chunk>,
+        Option<&FieldAsteroid>,
+    )>,
+    asset_server: Res<AssetServer>,
+    definitions: Res<Assets<AsteroidArchetype>>,
+    mut ships: Query<&mut Spaceship>,
+    mut rng: ResMut<SimulationRng>,
+    player_ship: Res<PlayerSpaceship>,
+    mut stats: ResMut<RunStats>,
+    mut generator: ResMut<LevelGenerator>,
 ) {
-    for (entity, hp, mut sprite, transform) in asteroids.iter_mut() {
-        sprite.index = 3 - hp.0 as usize;
-
-        if hp.is_dead() {
-            cmd.entity(entity)
-                .remove_bundle::<(Velocity, Collider, HitableByLaser, Hitpoints)>()
-                .insert(Lifetime::millis(200));
-
-            for i in 1..=5 {
-                let dir = (TAU / 5.0) * i as f32;
-                let dir = Quat::from_rotation_z(dir + (random::<f32>() - 1.0));
-
-                let rotation = Quat::from_rotation_z(random::<f32>() * TAU);
-
-                cmd.spawn_bundle(SpriteSheetBundle {
-                    texture_atlas: materials.asteroid.clone(),
-                    transform: Transform {
-                        translation: (transform.translation + dir * Vec3::new(10.0, 0., 0.))
-                            + Vec3::new(0., 0., -0.1),
-                        scale: Vec3::splat(0.3),
-                        rotation,
+    for death in deaths.iter() {
+        let (asteroid, transform, velocity, distance, chunk, field) =
+            match asteroids.get(death.entity) {
+                Ok(asteroid) => asteroid,
+                Err(_) => continue,
+            };
+
+        if let (Some(chunk), Some(field)) = (chunk, field) {
+            generator.asteroid_destroyed(chunk, *field);
+        }
+
+        cmd.entity(death.entity)
+            .remove_bundle::<(Velocity, Collider, HitableByLaser, Hitpoints)>()
+            .insert(Lifetime::millis(200));
+
+        let archetype = match definitions.get(&asteroid.archetype) {
+            Some(archetype) => archetype,
+            None => continue,
+        };
+
+        if let Some(mut ship) = death.killer.and_then(|killer| ships.get_mut(killer).ok()) {
+            ship.score += archetype.score;
+        }
+        // the stats are the player's, enemies shoot asteroids too
+        if death.killer == Some(player_ship.0) {
+            stats.asteroids_destroyed += 1;
+        }
+
+        if let Some(split) = &archetype.split {
+            let handle = asset_server.load(split.into.as_str());
+            let piece = match definitions.get(&handle) {
+                Some(piece) => piece,
+                None => {
+                    error!("not able to split asteroid, {} isn't loaded", split.into);
+                    continue;
+                }
+            };
+
+            let offset = rng.0.gen::<f32>() * TAU;
+            for i in 0..split.pieces {
+                let angle = offset + TAU * i as f32 / split.pieces as f32;
+                let dir = Quat::from_rotation_z(angle) * Vec3::Y;
+
+                let spin = piece.random_spin(&mut rng.0);
+                let entity = spawn_asteroid(
+                    &mut cmd,
+                    &handle,
+                    piece,
+                    Transform {
+                        translation: transform.translation + dir * 10. * archetype.scale,
+                        ..*transform
                     },
-                    ..default()
-                })
-                .insert(CleanupAfterGame)
-                .insert(Shard)
-                .insert(Velocity::from(dir * Vec3::Y * 15.0))
-                .insert(Lifetime::seconds(2))
-                .insert(MagnetAttractable);
+                    velocity.0 + dir * split.spread,
+                    spin,
+                    distance.map(|distance| distance.anchor),
+                );
 
+                // pieces of a field stay part of its chunk
+                if let Some(chunk) = chunk {
+                    cmd.entity(entity).insert(chunk.clone());
+                }
             }
+            continue;
+        }
+
+        let shards = archetype.shards;
+        for i in 1..=shards {
+            let dir = (TAU / shards as f32) * i as f32;
+            let dir = Quat::from_rotation_z(dir + (rng.0.gen::<f32>() - 1.0));
+
+            let rotation = Quat::from_rotation_z(rng.0.gen::<f32>() * TAU);
+
+            let shard = Transform {
+                translation: (transform.translation + dir * Vec3::new(10.0, 0., 0.))
+                    + Vec3::new(0., 0., -0.1),
+                scale: Vec3::splat(0.3),
+                rotation,
+            };
+
+            spawn_shard(
+                &mut cmd,
+                &asteroid.archetype,
+                archetype,
+                shard,
+                dir * Vec3::Y * 15.0,
+                archetype.random_spin(&mut rng.0),
+                Lifetime::seconds(2),
+            );
         }
     }
 }
 
-fn around(point: Vec3, radius: f32) -> Vec3 {
-    let dir = Quat::from_rotation_z(random::<f32>() * std::f32::consts::TAU);
+fn around(rng: &mut impl Rng, point: Vec3, radius: f32) -> Vec3 {
+    let dir = Quat::from_rotation_z(rng.gen::<f32>() * std::f32::consts::TAU);
 
     point + dir * Vec3::new(radius, 0., 0.0)
 }
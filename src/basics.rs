@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::simulation::SimulationTime;
+
 #[derive(Component, Default)]
 pub struct Velocity(pub Vec3);
 
@@ -9,8 +11,18 @@ impl From<Vec3> for Velocity {
     }
 }
 
-#[derive(Component)]
-pub struct Rotation(f32);
+/// How hard a body is to push around when bouncing off others.
+#[derive(Component, Clone, Copy)]
+pub struct Mass(pub f32);
+
+/// Damage dealt to whatever bounces off the body faster than `impacts.damage_speed`, on top of
+/// the damage of the impact itself.
+#[derive(Component, Clone, Copy)]
+pub struct ContactDamage(pub u32);
+
+/// Angular velocity around the z axis, in radians per second.
+#[derive(Component, Default)]
+pub struct Rotation(pub f32);
 
 impl From<f32> for Rotation {
     fn from(v: f32) -> Self {
@@ -55,11 +67,19 @@ impl Lifetime {
     }
 
     pub fn seconds(seconds: u32) -> Self {
+        Self::from_seconds(seconds as f32)
+    }
+
+    pub fn from_seconds(seconds: f32) -> Self {
         Self {
-            life_left: Timer::from_seconds(seconds as f32, false),
+            life_left: Timer::from_seconds(seconds, false),
             prevent_tick: false,
         }
     }
+
+    pub fn seconds_left(&self) -> f32 {
+        self.life_left.duration().as_secs_f32() - self.life_left.elapsed_secs()
+    }
 }
 
 #[derive(Component)]
@@ -68,29 +88,40 @@ pub struct MaximumDistanceFrom {
     pub distance: f32,
 }
 
+/// Changed only through [`crate::damage::DamageEvent`] and [`crate::damage::HealEvent`].
 #[derive(Component)]
-pub struct Hitpoints(pub u32);
+pub struct Hitpoints {
+    pub current: u32,
+    pub max: u32,
+}
 
 impl Hitpoints {
-    pub fn damage(&mut self, dmg: u32) {
-        self.0 = self.0.saturating_sub(dmg);
+    pub fn new(max: u32) -> Self {
+        Hitpoints { current: max, max }
     }
 
     pub fn is_dead(&self) -> bool {
-        self.0 == 0
+        self.current == 0
+    }
+
+    /// Portion of the hitpoints left, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        self.current as f32 / self.max.max(1) as f32
     }
 }
 
-pub fn movement(time: Res<Time>, mut query: Query<(&Velocity, &mut Transform)>) {
+pub fn movement(time: Res<SimulationTime>, mut query: Query<(&Velocity, &mut Transform)>) {
     for (velocity, mut transform) in query.iter_mut() {
         transform.translation += velocity.0 * time.delta_seconds();
     }
 }
 
-pub fn continuous_rotation(time: Res<Time>, mut query: Query<(&Rotation, &mut Transform)>) {
+pub fn continuous_rotation(
+    time: Res<SimulationTime>,
+    mut query: Query<(&Rotation, &mut Transform)>,
+) {
     for (rotation, mut transform) in query.iter_mut() {
-        let rotation = transform.rotation * Quat::from_rotation_z(rotation.0);
-        transform.rotation = transform.rotation.slerp(rotation, time.delta_seconds());
+        transform.rotate(Quat::from_rotation_z(rotation.0 * time.delta_seconds()));
     }
 }
 
@@ -107,7 +138,7 @@ pub fn sprite_animation(
 
 pub fn lifetime(
     mut commands: Commands,
-    time: Res<Time>,
+    time: Res<SimulationTime>,
     mut query: Query<(Entity, &mut Lifetime)>,
 ) {
     for (entity, mut lifetime) in query.iter_mut() {
@@ -129,10 +160,10 @@ pub fn maximum_distance_from(
     for (entity, transform, max_dist) in query.iter() {
         if let Ok(anchor_transform) = anchors.get(max_dist.anchor) {
             if transform.translation.distance(anchor_transform.translation) > max_dist.distance {
-                cmd.entity(entity).despawn();
+                cmd.entity(entity).despawn_recursive();
             }
         } else {
-            cmd.entity(entity).despawn();
+            cmd.entity(entity).despawn_recursive();
         }
     }
 }
@@ -0,0 +1,413 @@
+use bevy::asset::{AssetPath, LoadContext};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::asteroids::{spawn_shard, AsteroidArchetype, AtlasSprite};
+use crate::basics::{Hitpoints, Lifetime, MaximumDistanceFrom, Rotation, Velocity};
+use crate::collider::Collider;
+use crate::controls::Aim;
+use crate::damage::{DeathEvent, Resistances};
+use crate::director::Director;
+use crate::ron_asset::RonAsset;
+use crate::simulation::{LoadingAssets, SimulationRng};
+use crate::weapons::{spawn_weapon, AimTarget, WeaponDefinition, WeaponProgress, WeaponSystem};
+use crate::{CleanupAfterGame, HitableByLaser, PlayerSpaceship, Spaceship};
+
+/// Kind of boss, loaded from a `.boss.ron` file in the `bosses` folder.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "e5a07d3c-2b9f-4c61-9f3e-8d4b6a1c7e25"]
+pub struct BossArchetype {
+    /// How likely the director picks this archetype relative to the others.
+    pub weight: f32,
+    /// First wave the director sends this archetype in.
+    #[serde(default)]
+    pub first_wave: u32,
+    /// Distance the boss keeps from the player's ship.
+    pub preferred_distance: f32,
+    /// Parts open fire once the player's ship is closer than this.
+    pub fire_range: f32,
+    /// Score for destroying the whole boss.
+    pub score: u32,
+    /// Shards dropped once the last part is destroyed.
+    pub payout: Payout,
+    /// Shared by all the parts, each picks its own frame.
+    pub sprite: AtlasSprite,
+    pub parts: Vec<PartDefinition>,
+    /// Attack patterns in increasing order of parts destroyed.
+    pub phases: Vec<Phase>,
+}
+
+#[derive(Deserialize)]
+pub struct PartDefinition {
+    /// Position relative to the center of the boss, turning with it.
+    pub offset: Vec2,
+    pub frame: usize,
+    pub scale: f32,
+    pub hitpoints: u32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    /// Hitbox of the part before scaling.
+    pub collider: Collider,
+    /// Paths of the weapons mounted on the part, only the first one gets fired.
+    #[serde(default)]
+    pub weapons: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct Phase {
+    /// The phase starts once this many parts are destroyed.
+    pub destroyed: usize,
+    /// Speed of closing in on the preferred distance.
+    pub speed: f32,
+    /// Angular velocity in radians per second, negative spins clockwise.
+    pub spin: f32,
+    /// Indices of the parts firing during the phase.
+    pub firing: Vec<usize>,
+}
+
+impl Phase {
+    /// Parts of `alive` firing during the phase, all of them once the phase's own are destroyed,
+    /// so a boss never goes quiet.
+    pub fn firing_parts(&self, alive: &[usize]) -> Vec<usize> {
+        let firing: Vec<usize> = alive
+            .iter()
+            .copied()
+            .filter(|index| self.firing.contains(index))
+            .collect();
+
+        if firing.is_empty() {
+            alive.to_vec()
+        } else {
+            firing
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Payout {
+    /// Path of the asteroid archetype the shards look like.
+    pub shard: String,
+    pub count: u32,
+    /// Speed the shards fly apart with.
+    pub spread: f32,
+}
+
+impl RonAsset for BossArchetype {
+    const EXTENSIONS: &'static [&'static str] = &["boss.ron"];
+
+    fn load_textures(&mut self, load_context: &mut LoadContext) -> Vec<AssetPath<'static>> {
+        self.sprite.load_atlas(load_context)
+    }
+}
+
+impl BossArchetype {
+    /// The phase after `destroyed` parts were destroyed.
+    fn phase(&self, destroyed: usize) -> Option<&Phase> {
+        self.phases
+            .iter()
+            .take_while(|phase| phase.destroyed <= destroyed)
+            .last()
+    }
+
+    pub fn total_hitpoints(&self) -> u32 {
+        self.parts.iter().map(|part| part.hitpoints).sum()
+    }
+}
+
+/// All the boss archetypes, sorted by path so the director picks them deterministically.
+pub struct BossArchetypes(Vec<Handle<BossArchetype>>);
+
+impl FromWorld for BossArchetypes {
+    fn from_world(world: &mut World) -> Self {
+        BossArchetypes(LoadingAssets::load_folder(world, "bosses"))
+    }
+}
+
+impl BossArchetypes {
+    /// The archetypes already loaded, in path order.
+    pub fn loaded<'a>(
+        &'a self,
+        definitions: &'a Assets<BossArchetype>,
+    ) -> Vec<(&'a Handle<BossArchetype>, &'a BossArchetype)> {
+        self.0
+            .iter()
+            .filter_map(|handle| Some((handle, definitions.get(handle)?)))
+            .collect()
+    }
+}
+
+/// Center of a boss, the parts are its children.
+#[derive(Component)]
+pub struct Boss {
+    pub archetype: Handle<BossArchetype>,
+    /// Parts not destroyed yet.
+    parts_left: usize,
+}
+
+/// Part of a [`Boss`], a child entity at its offset from the boss.
+#[derive(Component)]
+pub struct BossPart {
+    pub boss: Entity,
+    /// Index into the parts of the archetype.
+    pub index: usize,
+}
+
+/// Spawns the boss of the current wave on a ring around the player's ship.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_wave_boss(
+    mut commands: Commands,
+    mut director: ResMut<Director>,
+    player_ship: Res<PlayerSpaceship>,
+    ships: Query<&Transform, With<Spaceship>>,
+    archetypes: Res<BossArchetypes>,
+    definitions: Res<Assets<BossArchetype>>,
+    weapons: Res<Assets<WeaponDefinition>>,
+    asset_server: Res<AssetServer>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    if !director.boss {
+        return;
+    }
+    director.boss = false;
+
+    let ship = match ships.get(player_ship.0) {
+        Ok(ship) => ship,
+        Err(_) => return,
+    };
+
+    let available: Vec<_> = archetypes
+        .loaded(&definitions)
+        .into_iter()
+        .filter(|(_, archetype)| archetype.first_wave <= director.wave)
+        .collect();
+
+    let weights = match WeightedIndex::new(available.iter().map(|(_, archetype)| archetype.weight))
+    {
+        Ok(weights) => weights,
+        Err(_) => {
+            warn!("no boss archetype available for wave {}", director.wave);
+            return;
+        }
+    };
+
+    let (handle, archetype) = available[weights.sample(&mut rng.0)];
+    let angle = rng.0.gen::<f32>() * std::f32::consts::TAU;
+    let position = ship.translation + Quat::from_rotation_z(angle) * Vec3::X * 1200.;
+
+    let boss = spawn_boss(
+        &mut commands,
+        handle,
+        Transform::from_translation(position),
+        player_ship.0,
+        archetype.parts.len(),
+    );
+    commands.entity(boss).with_children(|parent| {
+        for index in 0..archetype.parts.len() {
+            spawn_boss_part(parent, archetype, index, &weapons, &asset_server, &[]);
+        }
+    });
+}
+
+/// Spawns the center of a boss keeping close to `anchor`, with `parts_left` parts to be
+/// spawned as its children by [`spawn_boss_part`].
+pub fn spawn_boss(
+    commands: &mut Commands,
+    handle: &Handle<BossArchetype>,
+    transform: Transform,
+    anchor: Entity,
+    parts_left: usize,
+) -> Entity {
+    commands
+        .spawn_bundle(TransformBundle::from_transform(transform))
+        .insert(CleanupAfterGame)
+        .insert(Boss {
+            archetype: handle.clone(),
+            parts_left,
+        })
+        .insert(Velocity::default())
+        .insert(Rotation::default())
+        .insert(MaximumDistanceFrom {
+            anchor,
+            distance: 3000.0,
+        })
+        .id()
+}
+
+/// Spawns part `index` of `archetype` onto the boss building `parent`, its weapons pick up
+/// the `progress` of a saved run. `None` when the archetype has no such part.
+pub fn spawn_boss_part(
+    parent: &mut ChildBuilder,
+    archetype: &BossArchetype,
+    index: usize,
+    weapons: &Assets<WeaponDefinition>,
+    asset_server: &AssetServer,
+    progress: &[WeaponProgress],
+) -> Option<Entity> {
+    let part = archetype.parts.get(index)?;
+    let boss = parent.parent_entity();
+    let weapon_handles: Vec<Handle<WeaponDefinition>> = part
+        .weapons
+        .iter()
+        .map(|path| asset_server.load(path.as_str()))
+        .collect();
+
+    let entity = parent
+        .spawn_bundle(SpriteSheetBundle {
+            sprite: TextureAtlasSprite::new(part.frame),
+            texture_atlas: archetype.sprite.atlas.clone(),
+            transform: Transform {
+                translation: part.offset.extend(0.1),
+                scale: Vec3::splat(part.scale),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(CleanupAfterGame)
+        .insert(BossPart { boss, index })
+        .insert(Hitpoints::new(part.hitpoints))
+        .insert(part.resistances)
+        .insert(part.collider.clone())
+        .insert(HitableByLaser::default())
+        .insert(WeaponSystem::automatic(weapon_handles.len()))
+        .insert(AimTarget::default())
+        .with_children(|parent| {
+            let system = parent.parent_entity();
+            for (slot, weapon) in weapon_handles.iter().enumerate() {
+                spawn_weapon(parent, weapons, weapon, slot, system, progress.get(slot));
+            }
+        })
+        .id();
+
+    Some(entity)
+}
+
+/// Moves and spins bosses and picks the parts firing, following the phase they're in.
+#[allow(clippy::type_complexity)]
+pub fn boss_phases(
+    mut bosses: Query<(Entity, &Boss, &Transform, &mut Velocity, &mut Rotation)>,
+    mut parts: Query<(&BossPart, &mut WeaponSystem, &mut AimTarget)>,
+    player_ship: Res<PlayerSpaceship>,
+    ships: Query<&Transform, (With<Spaceship>, Without<Boss>)>,
+    definitions: Res<Assets<BossArchetype>>,
+) {
+    let target = match ships.get(player_ship.0) {
+        Ok(ship) => ship.translation,
+        Err(_) => return,
+    };
+
+    for (entity, boss, transform, mut velocity, mut rotation) in bosses.iter_mut() {
+        let archetype = match definitions.get(&boss.archetype) {
+            Some(archetype) => archetype,
+            None => continue,
+        };
+        let destroyed = archetype.parts.len().saturating_sub(boss.parts_left);
+        let phase = match archetype.phase(destroyed) {
+            Some(phase) => phase,
+            None => continue,
+        };
+
+        let mut offset = target - transform.translation;
+        offset.z = 0.;
+        let distance = offset.length();
+
+        // close in on the preferred distance, back off when closer
+        let approach = ((distance - archetype.preferred_distance) / archetype.preferred_distance)
+            .clamp(-1., 1.);
+        velocity.0 = offset.normalize_or_zero() * approach * phase.speed;
+        rotation.0 = phase.spin;
+
+        let alive: Vec<usize> = parts
+            .iter()
+            .filter(|(part, ..)| part.boss == entity)
+            .map(|(part, ..)| part.index)
+            .collect();
+        let firing = phase.firing_parts(&alive);
+
+        let in_range = distance <= archetype.fire_range;
+        for (part, mut weapons, mut aim) in parts.iter_mut() {
+            if part.boss == entity {
+                weapons.is_firing = in_range && firing.contains(&part.index);
+                aim.0 = Aim::Position(target);
+            }
+        }
+    }
+}
+
+/// Breaks off destroyed parts, the boss goes down with the last one and drops its payout.
+#[allow(clippy::too_many_arguments)]
+pub fn boss_part_destroyed(
+    mut cmd: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    parts: Query<&BossPart>,
+    mut bosses: Query<(&mut Boss, &Transform)>,
+    definitions: Res<Assets<BossArchetype>>,
+    shards: Res<Assets<AsteroidArchetype>>,
+    asset_server: Res<AssetServer>,
+    mut ships: Query<&mut Spaceship>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    for death in deaths.iter() {
+        let part = match parts.get(death.entity) {
+            Ok(part) => part,
+            Err(_) => continue,
+        };
+
+        // mounted weapons go down with the part
+        cmd.entity(death.entity).despawn_recursive();
+
+        let (mut boss, transform) = match bosses.get_mut(part.boss) {
+            Ok(boss) => boss,
+            Err(_) => continue,
+        };
+        boss.parts_left = boss.parts_left.saturating_sub(1);
+        if boss.parts_left > 0 {
+            continue;
+        }
+
+        cmd.entity(part.boss).despawn_recursive();
+
+        let archetype = match definitions.get(&boss.archetype) {
+            Some(archetype) => archetype,
+            None => continue,
+        };
+
+        if let Some(mut ship) = death.killer.and_then(|killer| ships.get_mut(killer).ok()) {
+            ship.score += archetype.score;
+        }
+
+        let payout = &archetype.payout;
+        let handle = asset_server.load(payout.shard.as_str());
+        let shard = match shards.get(&handle) {
+            Some(shard) => shard,
+            None => {
+                error!(
+                    "not able to drop boss payout, {} isn't loaded",
+                    payout.shard
+                );
+                continue;
+            }
+        };
+
+        for _ in 0..payout.count {
+            let dir = Quat::from_rotation_z(rng.0.gen::<f32>() * std::f32::consts::TAU) * Vec3::Y;
+            let speed = payout.spread * (0.5 + rng.0.gen::<f32>() * 0.5);
+
+            spawn_shard(
+                &mut cmd,
+                &handle,
+                shard,
+                Transform {
+                    translation: transform.translation + dir * 20. + Vec3::new(0., 0., -0.1),
+                    scale: Vec3::splat(0.3),
+                    ..default()
+                },
+                dir * speed,
+                shard.random_spin(&mut rng.0),
+                Lifetime::seconds(6),
+            );
+        }
+    }
+}
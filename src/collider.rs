@@ -0,0 +1,282 @@
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Shape of an entity for collisions, in the entity's local space.
+///
+/// Follows the translation, rotation and scale of the entity's `GlobalTransform`.
+#[derive(Component, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum Collider {
+    Circle(f32),
+    /// Box of the given size, centered on the entity.
+    Box(Vec2),
+    /// Convex polygon with its points in counter-clockwise order.
+    Polygon(Vec<Vec2>),
+}
+
+impl Collider {
+    /// The collider placed in the world by `transform`.
+    pub fn shape(&self, transform: &GlobalTransform) -> Shape {
+        let center = transform.translation.xy();
+
+        match self {
+            Collider::Circle(radius) => Shape::Circle {
+                center,
+                radius: radius * transform.scale.x.abs().max(transform.scale.y.abs()),
+            },
+            Collider::Box(size) => {
+                let half = *size * 0.5;
+                let corners = [
+                    Vec2::new(-half.x, -half.y),
+                    Vec2::new(half.x, -half.y),
+                    Vec2::new(half.x, half.y),
+                    Vec2::new(-half.x, half.y),
+                ];
+                Shape::polygon(transform, &corners)
+            }
+            Collider::Polygon(points) => Shape::polygon(transform, points),
+        }
+    }
+}
+
+/// A [`Collider`] placed in the world.
+#[derive(Clone, Debug)]
+pub enum Shape {
+    Circle { center: Vec2, radius: f32 },
+    Polygon(Vec<Vec2>),
+}
+
+impl Shape {
+    fn polygon(transform: &GlobalTransform, points: &[Vec2]) -> Self {
+        let mut points: Vec<Vec2> = points
+            .iter()
+            .map(|point| transform.mul_vec3(point.extend(0.)).xy())
+            .collect();
+
+        // mirroring flips the winding
+        if transform.scale.x * transform.scale.y < 0. {
+            points.reverse();
+        }
+
+        Shape::Polygon(points)
+    }
+
+    /// Axis aligned bounds as `(min, max)`.
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        match self {
+            Shape::Circle { center, radius } => (*center - *radius, *center + *radius),
+            Shape::Polygon(points) => points.iter().fold(
+                (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+                |(min, max), point| (min.min(*point), max.max(*point)),
+            ),
+        }
+    }
+
+    /// Point where the two shapes touch, `None` when they don't intersect.
+    pub fn contact(&self, other: &Shape) -> Option<Vec2> {
+        match (self, other) {
+            (
+                Shape::Circle { center, radius },
+                Shape::Circle {
+                    center: other_center,
+                    radius: other_radius,
+                },
+            ) => {
+                let offset = *other_center - *center;
+                if offset.length_squared() > (radius + other_radius).powi(2) {
+                    return None;
+                }
+
+                let total = radius + other_radius;
+                if total <= 0. {
+                    return Some(*center);
+                }
+                Some(*center + offset * (radius / total))
+            }
+            (Shape::Circle { center, radius }, Shape::Polygon(points))
+            | (Shape::Polygon(points), Shape::Circle { center, radius }) => {
+                circle_polygon_contact(*center, *radius, points)
+            }
+            (Shape::Polygon(points), Shape::Polygon(other_points)) => {
+                polygon_contact(points, other_points)
+            }
+        }
+    }
+
+    /// Shortest offset that moves `other` out of this shape, `None` when they don't overlap.
+    pub fn separation(&self, other: &Shape) -> Option<Vec2> {
+        let axes: Vec<Vec2> = self
+            .axes(other)
+            .into_iter()
+            .chain(other.axes(self))
+            .map(|axis| axis.normalize_or_zero())
+            .filter(|axis| *axis != Vec2::ZERO)
+            .collect();
+
+        let mut shortest: Option<(Vec2, f32)> = None;
+        for axis in axes {
+            let (min, max) = self.project(axis);
+            let (other_min, other_max) = other.project(axis);
+            let overlap = (max - other_min).min(other_max - min);
+            if overlap <= 0. {
+                return None;
+            }
+            if shortest.is_none_or(|(_, depth)| overlap < depth) {
+                shortest = Some((axis, overlap));
+            }
+        }
+
+        // push away from this shape
+        let (axis, depth) = shortest?;
+        if (other.center() - self.center()).dot(axis) < 0. {
+            Some(-axis * depth)
+        } else {
+            Some(axis * depth)
+        }
+    }
+
+    /// Candidate separating axes of this shape against `other`, not normalized.
+    fn axes(&self, other: &Shape) -> Vec<Vec2> {
+        match (self, other) {
+            (Shape::Polygon(points), _) => edges(points).map(|(a, b)| (b - a).perp()).collect(),
+            (Shape::Circle { center, .. }, Shape::Circle { center: other, .. }) => {
+                vec![*other - *center]
+            }
+            (Shape::Circle { center, .. }, Shape::Polygon(points)) => points
+                .iter()
+                .map(|point| *point - *center)
+                .min_by(|a, b| f32::total_cmp(&a.length_squared(), &b.length_squared()))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Interval the shape covers along the normalized `axis`.
+    fn project(&self, axis: Vec2) -> (f32, f32) {
+        match self {
+            Shape::Circle { center, radius } => {
+                let projected = center.dot(axis);
+                (projected - radius, projected + radius)
+            }
+            Shape::Polygon(points) => {
+                points
+                    .iter()
+                    .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), point| {
+                        let projected = point.dot(axis);
+                        (min.min(projected), max.max(projected))
+                    })
+            }
+        }
+    }
+
+    fn center(&self) -> Vec2 {
+        match self {
+            Shape::Circle { center, .. } => *center,
+            Shape::Polygon(points) if points.is_empty() => Vec2::ZERO,
+            Shape::Polygon(points) => points.iter().sum::<Vec2>() / points.len() as f32,
+        }
+    }
+
+    /// Closest point where the ray from `start` in the normalized direction `dir` enters the shape.
+    pub fn raycast(&self, start: Vec2, dir: Vec2) -> Option<Vec2> {
+        match self {
+            Shape::Circle { center, radius } => {
+                if start.distance_squared(*center) <= radius * radius {
+                    return Some(start);
+                }
+                crate::math::ray_circle_intersection(start, dir, *center, *radius)
+            }
+            Shape::Polygon(points) => {
+                if polygon_contains(points, start) {
+                    return Some(start);
+                }
+
+                edges(points)
+                    .filter_map(|(a, b)| crate::math::ray_segment_intersection(start, dir, a, b))
+                    .min_by(f32::total_cmp)
+                    .map(|t| start + dir * t)
+            }
+        }
+    }
+}
+
+fn edges(points: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+fn polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+    !points.is_empty() && edges(points).all(|(a, b)| (b - a).perp_dot(point - a) >= 0.)
+}
+
+fn closest_point_on_segment(a: Vec2, b: Vec2, point: Vec2) -> Vec2 {
+    let edge = b - a;
+    let length_squared = edge.length_squared();
+    if length_squared == 0. {
+        return a;
+    }
+
+    let t = ((point - a).dot(edge) / length_squared).clamp(0., 1.);
+    a + edge * t
+}
+
+fn circle_polygon_contact(center: Vec2, radius: f32, points: &[Vec2]) -> Option<Vec2> {
+    if polygon_contains(points, center) {
+        return Some(center);
+    }
+
+    let closest = edges(points)
+        .map(|(a, b)| closest_point_on_segment(a, b, center))
+        .min_by(|p1, p2| {
+            f32::total_cmp(&p1.distance_squared(center), &p2.distance_squared(center))
+        })?;
+
+    (closest.distance_squared(center) <= radius * radius).then_some(closest)
+}
+
+/// Separating axis test of two convex polygons.
+fn polygon_contact(points: &[Vec2], other_points: &[Vec2]) -> Option<Vec2> {
+    let project = |points: &[Vec2], axis: Vec2| {
+        points
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), point| {
+                let projected = point.dot(axis);
+                (min.min(projected), max.max(projected))
+            })
+    };
+
+    let separated = edges(points)
+        .chain(edges(other_points))
+        .map(|(a, b)| (b - a).perp())
+        .any(|axis| {
+            let (min, max) = project(points, axis);
+            let (other_min, other_max) = project(other_points, axis);
+            max < other_min || other_max < min
+        });
+
+    if separated || points.is_empty() || other_points.is_empty() {
+        return None;
+    }
+
+    // average of the corners reaching into the other polygon
+    let inside: Vec<Vec2> = points
+        .iter()
+        .filter(|point| polygon_contains(other_points, **point))
+        .chain(
+            other_points
+                .iter()
+                .filter(|point| polygon_contains(points, **point)),
+        )
+        .copied()
+        .collect();
+
+    if !inside.is_empty() {
+        return Some(inside.iter().sum::<Vec2>() / inside.len() as f32);
+    }
+
+    // only the edges cross, meet halfway between the centers
+    let centroid = |points: &[Vec2]| points.iter().sum::<Vec2>() / points.len() as f32;
+    Some((centroid(points) + centroid(other_points)) * 0.5)
+}
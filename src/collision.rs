@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::basics::{ContactDamage, Mass, Velocity};
+use crate::collider::{Collider, Shape};
+use crate::config::GameConfig;
+use crate::damage::{DamageEvent, DamageKind};
+use crate::spatial::SpatialIndex;
+
+/// Two colliders started touching this tick.
+#[derive(Clone, Copy, Debug)]
+pub struct CollisionStarted {
+    pub a: Entity,
+    pub b: Entity,
+    pub point: Vec2,
+}
+
+/// Two colliders stopped touching this tick.
+///
+/// Also sent when one of them got despawned or lost its collider, so either entity
+/// may no longer exist.
+#[derive(Clone, Copy, Debug)]
+pub struct CollisionEnded {
+    pub a: Entity,
+    pub b: Entity,
+    /// Where they touched last.
+    pub point: Vec2,
+}
+
+impl CollisionStarted {
+    /// Both entities, ordered so the first one satisfies `first` and the second one `second`.
+    pub fn matching(
+        &self,
+        first: impl Fn(Entity) -> bool,
+        second: impl Fn(Entity) -> bool,
+    ) -> Option<(Entity, Entity)> {
+        matching(self.a, self.b, first, second)
+    }
+}
+
+impl CollisionEnded {
+    /// Both entities, ordered so the first one satisfies `first` and the second one `second`.
+    pub fn matching(
+        &self,
+        first: impl Fn(Entity) -> bool,
+        second: impl Fn(Entity) -> bool,
+    ) -> Option<(Entity, Entity)> {
+        matching(self.a, self.b, first, second)
+    }
+}
+
+fn matching(
+    a: Entity,
+    b: Entity,
+    first: impl Fn(Entity) -> bool,
+    second: impl Fn(Entity) -> bool,
+) -> Option<(Entity, Entity)> {
+    if first(a) && second(b) {
+        Some((a, b))
+    } else if first(b) && second(a) {
+        Some((b, a))
+    } else {
+        None
+    }
+}
+
+/// Pairs of colliders touching at the end of the last tick, with the lower entity first.
+#[derive(Default)]
+pub struct Contacts(BTreeMap<(Entity, Entity), Vec2>);
+
+/// Finds every pair of touching colliders and sends events for the pairs that changed.
+pub fn detect_collisions(
+    index: Res<SpatialIndex>,
+    colliders: Query<(Entity, &GlobalTransform, &Collider)>,
+    mut contacts: ResMut<Contacts>,
+    mut started: EventWriter<CollisionStarted>,
+    mut ended: EventWriter<CollisionEnded>,
+) {
+    let shapes: HashMap<Entity, Shape> = colliders
+        .iter()
+        .map(|(entity, transform, collider)| (entity, collider.shape(transform)))
+        .collect();
+
+    let mut entities: Vec<Entity> = shapes.keys().copied().collect();
+    entities.sort_unstable();
+
+    let mut touching = BTreeMap::new();
+    for a in entities {
+        let shape = &shapes[&a];
+        for b in index.query_shape(shape) {
+            if b <= a {
+                continue;
+            }
+
+            let other = match shapes.get(&b) {
+                Some(other) => other,
+                None => continue,
+            };
+
+            if let Some(point) = shape.contact(other) {
+                touching.insert((a, b), point);
+            }
+        }
+    }
+
+    for (&(a, b), &point) in touching.iter() {
+        if !contacts.0.contains_key(&(a, b)) {
+            started.send(CollisionStarted { a, b, point });
+        }
+    }
+
+    for (&(a, b), &point) in contacts.0.iter() {
+        if !touching.contains_key(&(a, b)) {
+            ended.send(CollisionEnded { a, b, point });
+        }
+    }
+
+    contacts.0 = touching;
+}
+
+/// Portion of the overlap of two bodies resolved each tick, the rest is left for later ticks
+/// so resting bodies don't jitter.
+const CORRECTION: f32 = 0.8;
+
+/// Bounces bodies with a [`Mass`] off each other and pushes them apart while they touch,
+/// impacts faster than `impacts.damage_speed` damage both of them.
+///
+/// Goes over all the [`Contacts`] every tick, not only the new ones, so bodies pushed into
+/// each other can't end up passing through.
+pub fn bounce(
+    contacts: Res<Contacts>,
+    mut bodies: Query<(
+        &mut Transform,
+        &GlobalTransform,
+        &Collider,
+        &mut Velocity,
+        &Mass,
+    )>,
+    contact_damage: Query<&ContactDamage>,
+    config: Res<GameConfig>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let impacts = &config.impacts;
+
+    for &(a, b) in contacts.0.keys() {
+        let body = |entity| {
+            let (_, transform, collider, velocity, mass) = bodies.get(entity).ok()?;
+            Some((collider.shape(transform), velocity.0, mass.0))
+        };
+        let ((a_shape, a_velocity, a_mass), (b_shape, b_velocity, b_mass)) =
+            match (body(a), body(b)) {
+                (Some(a), Some(b)) => (a, b),
+                _ => continue,
+            };
+
+        let separation = match a_shape.separation(&b_shape) {
+            Some(separation) => separation.extend(0.),
+            None => continue,
+        };
+        let normal = separation.normalize_or_zero();
+        if normal == Vec3::ZERO {
+            continue;
+        }
+
+        // the lighter body gets pushed further
+        let (a_share, b_share) = (1. / a_mass, 1. / b_mass);
+        let correction = separation * CORRECTION / (a_share + b_share);
+        if let Ok((mut transform, ..)) = bodies.get_mut(a) {
+            transform.translation -= correction * a_share;
+        }
+        if let Ok((mut transform, ..)) = bodies.get_mut(b) {
+            transform.translation += correction * b_share;
+        }
+
+        // already moving apart
+        let closing_speed = (a_velocity - b_velocity).dot(normal);
+        if closing_speed <= 0. {
+            continue;
+        }
+
+        let impulse = (1. + impacts.restitution) * closing_speed / (a_share + b_share);
+        if let Ok((_, _, _, mut velocity, _)) = bodies.get_mut(a) {
+            velocity.0 -= normal * impulse * a_share;
+        }
+        if let Ok((_, _, _, mut velocity, _)) = bodies.get_mut(b) {
+            velocity.0 += normal * impulse * b_share;
+        }
+
+        let threshold = match impacts.damage_speed {
+            Some(threshold) if closing_speed > threshold => threshold,
+            _ => continue,
+        };
+
+        let amount = ((closing_speed - threshold) * impacts.damage_per_speed).round() as u32;
+        for (target, source) in [(a, b), (b, a)] {
+            let extra = contact_damage.get(source).map_or(0, |contact| contact.0);
+            damage.send(DamageEvent {
+                target,
+                source: Some(source),
+                amount: amount + extra,
+                kind: DamageKind::Kinetic,
+            });
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use serde::{Deserialize, Serialize};
+
+use crate::basics::Mass;
+use crate::collider::Collider;
+use crate::damage::Resistances;
+use crate::magnet::Magnet;
+use crate::replay::ReplayPlayer;
+use crate::ron_asset::RonAsset;
+use crate::simulation::LoadingAssets;
+use crate::Spaceship;
+
+const GAME_CONFIG: &str = "game.config.ron";
+
+/// Gameplay tuning, loaded from `assets/game.config.ron` and updated live when the file changes.
+#[derive(Deserialize, Serialize, TypeUuid, Clone, Debug, Default)]
+#[uuid = "3d7a9f52-8c1e-4b6a-a0f4-6e2b9c4d1f87"]
+#[serde(default)]
+pub struct GameConfig {
+    pub ship: ShipConfig,
+    pub waves: WaveConfig,
+    pub impacts: ImpactConfig,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct ShipConfig {
+    /// Velocity gained (or lost when braking) per second.
+    pub speed_gain: f32,
+    /// Maximum speed in any direction.
+    pub max_speed: f32,
+    /// Portion of the velocity lost per second.
+    pub drag: f32,
+    pub flight_model: FlightModel,
+    /// Radians per second² the heading can speed up or slow down its turning,
+    /// the ship faces the aim instantly when 0.
+    pub turn_acceleration: f32,
+    /// Maximum radians per second the heading turns.
+    pub max_turn_speed: f32,
+    /// Hitpoints the ship starts with.
+    pub hitpoints: u32,
+    pub collider: Collider,
+    pub mass: f32,
+    pub resistances: Resistances,
+    /// Hitpoints restored by each collected shard.
+    pub shard_healing: u32,
+    pub magnet_force: f32,
+    pub magnet_distance: f32,
+    /// Paths of the weapons the ship starts with, in slot order.
+    pub weapons: Vec<String>,
+}
+
+/// Waves of asteroids the director sends at the player.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct WaveConfig {
+    /// Difficulty at given waves in increasing order, waves in between interpolate
+    /// and later ones keep the last point.
+    pub curve: Vec<WavePoint>,
+    /// Seconds of rest before the first wave.
+    pub warmup: f32,
+    /// Seconds of rest between two waves.
+    pub rest: f32,
+    /// Seconds after which the next wave comes, even when asteroids of the current one are left.
+    pub max_duration: f32,
+    /// Rest gets up to this many times longer the more hitpoints the ship is missing.
+    pub mercy: f32,
+    /// Every this many waves a boss comes along, never when 0.
+    pub boss_every: u32,
+    /// Waves the difficulty gets ahead of the wave number per minute of the run.
+    pub waves_per_minute: f32,
+    /// Waves the difficulty gets ahead of the wave number per point of score.
+    pub waves_per_score: f32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+pub struct WavePoint {
+    pub wave: u32,
+    /// Number of asteroids in the wave.
+    pub count: f32,
+    /// Multiplier of the archetype speed.
+    pub speed: f32,
+    /// Seconds between two asteroids of the wave.
+    pub interval: f32,
+    /// Number of enemy ships in the wave.
+    #[serde(default)]
+    pub enemies: f32,
+}
+
+impl WaveConfig {
+    /// Difficulty of `wave` on the curve, pushed further along by long runs and high scores.
+    pub fn at(&self, wave: u32, run_seconds: f32, score: u32) -> WavePoint {
+        let progress = wave as f32
+            + self.waves_per_minute * run_seconds / 60.
+            + self.waves_per_score * score as f32;
+
+        let next = self
+            .curve
+            .iter()
+            .position(|point| point.wave as f32 > progress);
+        let (from, to) = match next {
+            Some(i) if i > 0 => (self.curve[i - 1], self.curve[i]),
+            Some(_) => (self.curve[0], self.curve[0]),
+            None => match self.curve.last() {
+                Some(last) => (*last, *last),
+                None => return WavePoint::default(),
+            },
+        };
+
+        let span = to.wave.saturating_sub(from.wave).max(1);
+        let t = ((progress - from.wave as f32) / span as f32).clamp(0., 1.);
+        WavePoint {
+            wave,
+            count: from.count + (to.count - from.count) * t,
+            speed: from.speed + (to.speed - from.speed) * t,
+            interval: from.interval + (to.interval - from.interval) * t,
+            enemies: from.enemies + (to.enemies - from.enemies) * t,
+        }
+    }
+}
+
+/// Bouncing of ships and asteroids off each other.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct ImpactConfig {
+    /// Portion of the speed along the impact kept after bouncing, 1 is perfectly elastic.
+    pub restitution: f32,
+    /// Closing speed above which both sides take damage, impacts never damage when `None`.
+    pub damage_speed: Option<f32>,
+    /// Damage per unit of closing speed above `damage_speed`.
+    pub damage_per_speed: f32,
+}
+
+/// How the ship behaves while not thrusting.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlightModel {
+    /// Brakes down to a stop.
+    Assisted,
+    /// Keeps drifting, only slowed down by drag.
+    Newtonian,
+}
+
+impl Default for WaveConfig {
+    fn default() -> Self {
+        WaveConfig {
+            curve: vec![WavePoint::default()],
+            warmup: 3.,
+            rest: 8.,
+            max_duration: 60.,
+            mercy: 2.,
+            boss_every: 0,
+            waves_per_minute: 0.,
+            waves_per_score: 0.,
+        }
+    }
+}
+
+impl Default for WavePoint {
+    fn default() -> Self {
+        WavePoint {
+            wave: 1,
+            count: 5.,
+            speed: 1.,
+            interval: 1.,
+            enemies: 0.,
+        }
+    }
+}
+
+impl Default for ImpactConfig {
+    fn default() -> Self {
+        ImpactConfig {
+            restitution: 1.,
+            damage_speed: None,
+            damage_per_speed: 0.1,
+        }
+    }
+}
+
+impl Default for ShipConfig {
+    fn default() -> Self {
+        ShipConfig {
+            speed_gain: 1000.,
+            max_speed: 500.,
+            drag: 0.,
+            flight_model: FlightModel::Assisted,
+            turn_acceleration: 0.,
+            max_turn_speed: TAU,
+            hitpoints: 100,
+            collider: Collider::Box(Vec2::new(16., 48.)),
+            mass: 1.,
+            resistances: Resistances::default(),
+            shard_healing: 0,
+            magnet_force: 250.,
+            magnet_distance: 150.,
+            weapons: vec![
+                "weapons/cannon.weapon.ron".to_string(),
+                "weapons/laser.weapon.ron".to_string(),
+                "weapons/missile.weapon.ron".to_string(),
+            ],
+        }
+    }
+}
+
+impl RonAsset for GameConfig {
+    const EXTENSIONS: &'static [&'static str] = &["config.ron"];
+}
+
+impl ShipConfig {
+    pub fn magnet(&self) -> Magnet {
+        Magnet {
+            force: self.magnet_force,
+            max_distance: self.magnet_distance,
+        }
+    }
+}
+
+pub struct GameConfigHandle(Handle<GameConfig>);
+
+impl FromWorld for GameConfigHandle {
+    fn from_world(world: &mut World) -> Self {
+        let handle = world.resource::<AssetServer>().load(GAME_CONFIG);
+        world.resource_mut::<LoadingAssets>().track(&handle);
+        GameConfigHandle(handle)
+    }
+}
+
+/// Copies the loaded config into the [`GameConfig`] resource once loading is done.
+///
+/// Runs as the loading ends, the asset event would only arrive after the game already started.
+pub fn init_game_config(
+    handle: Res<GameConfigHandle>,
+    configs: Res<Assets<GameConfig>>,
+    mut config: ResMut<GameConfig>,
+) {
+    if let Some(loaded) = configs.get(&handle.0) {
+        *config = loaded.clone();
+    }
+}
+
+/// Copies the reloaded config into the [`GameConfig`] resource and onto the ships already flying.
+///
+/// Replays keep the config they were recorded with, the file changing doesn't affect them.
+pub fn apply_game_config(
+    mut events: EventReader<AssetEvent<GameConfig>>,
+    replay: Option<Res<ReplayPlayer>>,
+    handle: Res<GameConfigHandle>,
+    configs: Res<Assets<GameConfig>>,
+    mut config: ResMut<GameConfig>,
+    mut ships: Query<(&mut Magnet, &mut Collider, &mut Mass, &mut Resistances), With<Spaceship>>,
+) {
+    for event in events.iter() {
+        if !matches!(event, AssetEvent::Modified { handle: h } if *h == handle.0)
+            || replay.is_some()
+        {
+            continue;
+        }
+
+        if let Some(loaded) = configs.get(&handle.0) {
+            *config = loaded.clone();
+
+            for (mut magnet, mut collider, mut mass, mut resistances) in ships.iter_mut() {
+                *magnet = config.ship.magnet();
+                *collider = config.ship.collider.clone();
+                *mass = Mass(config.ship.mass);
+                *resistances = config.ship.resistances;
+            }
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use bevy::input::gamepad::{GamepadEvent, GamepadEventType};
+use bevy::input::mouse::MouseWheel;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{AppState, MouseWorldPos};
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputBindings::load_or_default())
+            .init_resource::<ActionState>()
+            .add_system_to_stage(CoreStage::PreUpdate, update_action_state.after(InputSystem))
+            .add_system(save_bindings)
+            .add_system(gamepad_connections)
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(clear_presses))
+            .add_system_set(SystemSet::on_enter(AppState::InGame).with_system(clear_presses));
+    }
+}
+
+/// Everything the player can do, gameplay never looks at the raw input devices.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+    ThrustUp,
+    ThrustDown,
+    ThrustLeft,
+    ThrustRight,
+    ThrustToAim,
+    Fire,
+    NextWeapon,
+    PrevWeapon,
+    Pause,
+    /// Saves the run and returns to the menu.
+    Quit,
+    Confirm,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    WheelUp,
+    WheelDown,
+    /// Button on any of the connected gamepads.
+    Gamepad(GamepadButtonType),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+impl Stick {
+    fn value(self, gamepad: Gamepad, axes: &Axis<GamepadAxis>) -> Vec2 {
+        let (x, y) = match self {
+            Stick::Left => (GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY),
+            Stick::Right => (GamepadAxisType::RightStickX, GamepadAxisType::RightStickY),
+        };
+
+        Vec2::new(
+            axes.get(GamepadAxis(gamepad, x)).unwrap_or(0.),
+            axes.get(GamepadAxis(gamepad, y)).unwrap_or(0.),
+        )
+    }
+}
+
+/// Which inputs trigger which action, stored in the user's config directory.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct InputBindings {
+    pub bindings: BTreeMap<Action, Vec<Binding>>,
+    pub thrust_stick: Option<Stick>,
+    pub aim_stick: Option<Stick>,
+    /// Stick deflection below which the stick counts as released.
+    pub stick_deadzone: f32,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use Action::*;
+        use Binding::*;
+
+        InputBindings {
+            bindings: BTreeMap::from([
+                (ThrustUp, vec![Key(KeyCode::W)]),
+                (ThrustDown, vec![Key(KeyCode::S)]),
+                (ThrustLeft, vec![Key(KeyCode::A)]),
+                (ThrustRight, vec![Key(KeyCode::D)]),
+                (
+                    ThrustToAim,
+                    vec![
+                        Mouse(MouseButton::Right),
+                        Gamepad(GamepadButtonType::LeftTrigger2),
+                    ],
+                ),
+                (
+                    Fire,
+                    vec![
+                        Mouse(MouseButton::Left),
+                        Gamepad(GamepadButtonType::RightTrigger2),
+                    ],
+                ),
+                (
+                    NextWeapon,
+                    vec![WheelUp, Gamepad(GamepadButtonType::RightTrigger)],
+                ),
+                (
+                    PrevWeapon,
+                    vec![WheelDown, Gamepad(GamepadButtonType::LeftTrigger)],
+                ),
+                (
+                    Pause,
+                    vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::Start)],
+                ),
+                (
+                    Quit,
+                    vec![Key(KeyCode::Q), Gamepad(GamepadButtonType::Select)],
+                ),
+                (
+                    Confirm,
+                    vec![
+                        Key(KeyCode::Return),
+                        Key(KeyCode::Space),
+                        Gamepad(GamepadButtonType::South),
+                        Gamepad(GamepadButtonType::Start),
+                    ],
+                ),
+            ]),
+            thrust_stick: Some(Stick::Left),
+            aim_stick: Some(Stick::Right),
+            stick_deadzone: 0.2,
+        }
+    }
+}
+
+impl InputBindings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("asteroids").join("controls.ron"))
+    }
+
+    /// Loads the user's bindings, falling back to (and saving) the defaults.
+    pub fn load_or_default() -> Self {
+        let path = match InputBindings::path() {
+            Some(path) => path,
+            None => return InputBindings::default(),
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => match ron::from_str(&content) {
+                Ok(bindings) => return bindings,
+                Err(e) => eprintln!("not able to parse {}: {}", path.display(), e),
+            },
+            Err(_) => InputBindings::default().save(),
+        }
+
+        InputBindings::default()
+    }
+
+    pub fn save(&self) {
+        let path = match InputBindings::path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let content = ron::ser::to_string_pretty(self, Default::default())
+            .expect("bindings are always serializable");
+
+        let result = std::fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| std::fs::write(&path, content));
+
+        if let Err(e) = result {
+            eprintln!("not able to save {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Current state of every [`Action`], updated once per frame from the input devices.
+#[derive(Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    presses: HashMap<Action, u32>,
+    /// Thrust from the thrust stick.
+    pub thrust: Vec2,
+    /// Direction of the aim stick, `None` while aiming with the cursor.
+    pub aim_direction: Option<Vec2>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// How many times the action was triggered since the last call.
+    ///
+    /// Presses are kept until taken, so frames without a simulation tick don't lose them.
+    pub fn take_presses(&mut self, action: Action) -> u32 {
+        self.presses.remove(&action).unwrap_or(0)
+    }
+
+    pub fn clear_presses(&mut self) {
+        self.presses.clear();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_action_state(
+    bindings: Res<InputBindings>,
+    keys: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut scroll_reader: EventReader<MouseWheel>,
+    mut cursor_moved_reader: EventReader<CursorMoved>,
+    mut state: ResMut<ActionState>,
+) {
+    let (mut wheel_up, mut wheel_down) = (0, 0);
+    for event in scroll_reader.iter() {
+        match event.y.total_cmp(&0.) {
+            std::cmp::Ordering::Less => wheel_down += 1,
+            std::cmp::Ordering::Greater => wheel_up += 1,
+            _ => continue,
+        }
+    }
+
+    let state = &mut *state;
+    state.pressed.clear();
+
+    for (action, bindings) in bindings.bindings.iter() {
+        for binding in bindings {
+            let (pressed, presses) = match *binding {
+                Binding::Key(key) => (keys.pressed(key), keys.just_pressed(key) as u32),
+                Binding::Mouse(button) => (
+                    mouse_input.pressed(button),
+                    mouse_input.just_pressed(button) as u32,
+                ),
+                Binding::WheelUp => (false, wheel_up),
+                Binding::WheelDown => (false, wheel_down),
+                Binding::Gamepad(button) => gamepads.iter().fold((false, 0), |acc, gamepad| {
+                    let button = GamepadButton(*gamepad, button);
+                    (
+                        acc.0 || gamepad_buttons.pressed(button),
+                        acc.1 + gamepad_buttons.just_pressed(button) as u32,
+                    )
+                }),
+            };
+
+            if pressed {
+                state.pressed.insert(*action);
+            }
+
+            if presses > 0 {
+                *state.presses.entry(*action).or_default() += presses;
+            }
+        }
+    }
+
+    let deadzone = bindings.stick_deadzone;
+    let stick_value = |stick: Option<Stick>| {
+        let stick = stick?;
+        gamepads
+            .iter()
+            .map(|gamepad| stick.value(*gamepad, &gamepad_axes))
+            .find(|value| value.length() > deadzone)
+    };
+
+    state.thrust = stick_value(bindings.thrust_stick)
+        .unwrap_or(Vec2::ZERO)
+        .clamp_length_max(1.);
+
+    if let Some(aim) = stick_value(bindings.aim_stick) {
+        state.aim_direction = Some(aim.normalize());
+    } else if cursor_moved_reader.iter().next().is_some() {
+        state.aim_direction = None;
+    }
+}
+
+fn clear_presses(mut state: ResMut<ActionState>) {
+    state.clear_presses();
+}
+
+fn gamepad_connections(mut gamepad_events: EventReader<GamepadEvent>) {
+    for GamepadEvent(gamepad, event) in gamepad_events.iter() {
+        match event {
+            GamepadEventType::Connected => info!("gamepad {} connected", gamepad.0),
+            GamepadEventType::Disconnected => info!("gamepad {} disconnected", gamepad.0),
+            _ => {}
+        }
+    }
+}
+
+fn save_bindings(bindings: Res<InputBindings>) {
+    if bindings.is_changed() && !bindings.is_added() {
+        bindings.save();
+    }
+}
+
+/// Everything the player did during a single simulation tick.
+///
+/// Gameplay systems only ever read this, never the input devices directly,
+/// which makes it possible to feed them recorded input instead.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct PlayerInput {
+    /// Thrust direction, not normalized.
+    pub thrust: Vec2,
+    /// Thrust towards the aim instead of using `thrust`.
+    pub thrust_to_aim: bool,
+    pub aim: Aim,
+    pub firing: bool,
+    /// How many weapons to switch forward (or backward when negative).
+    pub switch_weapon: i8,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Aim {
+    /// World position to aim at, e.g. the cursor.
+    Position(Vec3),
+    /// Normalized direction to aim in, e.g. from a stick.
+    Direction(Vec2),
+}
+
+impl Aim {
+    /// Normalized direction from `pos` towards the aim, zero when aiming right at `pos`.
+    pub fn dir_from(&self, pos: Vec3) -> Vec3 {
+        match *self {
+            Aim::Position(aim) => {
+                let mut dir = aim - pos;
+                dir.z = 0.;
+                dir.normalize_or_zero()
+            }
+            Aim::Direction(dir) => dir.extend(0.),
+        }
+    }
+}
+
+impl Default for Aim {
+    fn default() -> Self {
+        Aim::Position(Vec3::ZERO)
+    }
+}
+
+pub fn sample_player_input(
+    mut actions: ResMut<ActionState>,
+    mouse_pos: Res<MouseWorldPos>,
+    mut input: ResMut<PlayerInput>,
+) {
+    let mut thrust = Vec2::ZERO;
+
+    if actions.pressed(Action::ThrustUp) {
+        thrust.y += 1.;
+    }
+
+    if actions.pressed(Action::ThrustLeft) {
+        thrust.x -= 1.;
+    }
+
+    if actions.pressed(Action::ThrustDown) {
+        thrust.y -= 1.;
+    }
+
+    if actions.pressed(Action::ThrustRight) {
+        thrust.x += 1.;
+    }
+
+    thrust += actions.thrust;
+
+    let aim = match actions.aim_direction {
+        Some(direction) => Aim::Direction(direction),
+        None => Aim::Position(mouse_pos.0),
+    };
+
+    let next = actions.take_presses(Action::NextWeapon) as i32;
+    let prev = actions.take_presses(Action::PrevWeapon) as i32;
+
+    *input = PlayerInput {
+        thrust,
+        thrust_to_aim: actions.pressed(Action::ThrustToAim),
+        aim,
+        firing: actions.pressed(Action::Fire),
+        switch_weapon: (next - prev).clamp(i8::MIN as i32, i8::MAX as i32) as i8,
+    };
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::basics::Hitpoints;
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DamageKind {
+    Kinetic,
+    Energy,
+    Explosive,
+}
+
+/// Request to damage `target`, the only way gameplay takes hitpoints away.
+#[derive(Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    /// Who gets the credit when the target dies.
+    pub source: Option<Entity>,
+    pub amount: u32,
+    pub kind: DamageKind,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HealEvent {
+    pub target: Entity,
+    pub amount: u32,
+}
+
+/// Sent once when an entity's hitpoints drop to zero.
+#[derive(Clone, Copy, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    /// Source of the killing blow.
+    pub killer: Option<Entity>,
+}
+
+/// Portion of each kind of damage the entity ignores, from 0 to 1.
+#[derive(Component, Deserialize, Serialize, Clone, Copy, Default, Debug)]
+#[serde(default)]
+pub struct Resistances {
+    pub kinetic: f32,
+    pub energy: f32,
+    pub explosive: f32,
+}
+
+impl Resistances {
+    fn reduce(&self, amount: u32, kind: DamageKind) -> u32 {
+        let resistance = match kind {
+            DamageKind::Kinetic => self.kinetic,
+            DamageKind::Energy => self.energy,
+            DamageKind::Explosive => self.explosive,
+        };
+
+        (amount as f32 * (1. - resistance.clamp(0., 1.))).round() as u32
+    }
+}
+
+pub fn apply_damage(
+    mut damage_events: EventReader<DamageEvent>,
+    mut heal_events: EventReader<HealEvent>,
+    mut targets: Query<(&mut Hitpoints, Option<&Resistances>)>,
+    mut deaths: EventWriter<DeathEvent>,
+) {
+    for heal in heal_events.iter() {
+        if let Ok((mut hp, _)) = targets.get_mut(heal.target) {
+            if !hp.is_dead() {
+                hp.current = (hp.current + heal.amount).min(hp.max);
+            }
+        }
+    }
+
+    for damage in damage_events.iter() {
+        let (mut hp, resistances) = match targets.get_mut(damage.target) {
+            Ok(target) => target,
+            Err(_) => continue,
+        };
+
+        if hp.is_dead() {
+            continue;
+        }
+
+        let amount = resistances.map_or(damage.amount, |r| r.reduce(damage.amount, damage.kind));
+        hp.current = hp.current.saturating_sub(amount);
+
+        if hp.is_dead() {
+            deaths.send(DeathEvent {
+                entity: damage.target,
+                killer: damage.source,
+            });
+        }
+    }
+}
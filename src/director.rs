@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use serde::{Deserialize, Serialize};
+
+use crate::asteroids::{spawn_incoming_asteroid, Asteroid, AsteroidArchetype, AsteroidArchetypes};
+use crate::basics::{Hitpoints, MaximumDistanceFrom};
+use crate::bosses::Boss;
+use crate::config::{GameConfig, WaveConfig};
+use crate::enemies::Enemy;
+use crate::high_scores::RunStats;
+use crate::simulation::{SimulationRng, SimulationTime};
+use crate::{PlayerSpaceship, Spaceship};
+
+/// Sends waves of asteroids at the player, with rest periods in between.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Director {
+    /// Current wave, 0 before the first one.
+    pub wave: u32,
+    /// Asteroids of the current wave still to spawn.
+    remaining: u32,
+    /// Enemy ships of the current wave still to spawn.
+    #[serde(default)]
+    pub enemies: u32,
+    /// A boss of the current wave is still to spawn.
+    #[serde(default)]
+    pub boss: bool,
+    /// Speed multiplier of the current wave.
+    speed: f32,
+    /// Seconds between spawns of the current wave.
+    interval: f32,
+    /// Seconds until the next spawn, or the next wave while resting.
+    cooldown: f32,
+    /// Seconds since the current wave started.
+    elapsed: f32,
+    resting: bool,
+}
+
+impl Director {
+    pub fn new(config: &WaveConfig) -> Self {
+        Director {
+            cooldown: config.warmup,
+            resting: true,
+            ..default()
+        }
+    }
+
+    fn start_wave(&mut self, config: &WaveConfig, run_seconds: f32, score: u32) {
+        self.wave += 1;
+
+        let point = config.at(self.wave, run_seconds, score);
+        self.remaining = point.count.round().max(1.) as u32;
+        self.enemies = point.enemies.round() as u32;
+        self.boss = config.boss_every > 0 && self.wave.is_multiple_of(config.boss_every);
+        self.speed = point.speed;
+        self.interval = point.interval;
+        self.cooldown = 0.;
+        self.elapsed = 0.;
+        self.resting = false;
+
+        info!(
+            "wave {}: {} asteroids, {} enemies{}",
+            self.wave,
+            self.remaining,
+            self.enemies,
+            if self.boss { " and a boss" } else { "" }
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn direct_waves(
+    mut commands: Commands,
+    mut director: ResMut<Director>,
+    player_ship: Res<PlayerSpaceship>,
+    ships: Query<(&Transform, &Hitpoints, &Spaceship)>,
+    incoming: Query<
+        (),
+        (
+            Or<(With<Asteroid>, With<Enemy>, With<Boss>)>,
+            With<MaximumDistanceFrom>,
+        ),
+    >,
+    archetypes: Res<AsteroidArchetypes>,
+    definitions: Res<Assets<AsteroidArchetype>>,
+    mut rng: ResMut<SimulationRng>,
+    time: Res<SimulationTime>,
+    stats: Res<RunStats>,
+    config: Res<GameConfig>,
+) {
+    let config = &config.waves;
+    let (ship_transform, hp, ship) = match ships.get(player_ship.0) {
+        Ok(ship) => ship,
+        Err(_) => return,
+    };
+
+    director.cooldown -= time.delta_seconds();
+    director.elapsed += time.delta_seconds();
+
+    if director.resting {
+        if director.cooldown <= 0. {
+            let run_seconds = stats.ticks as f32 * time.delta_seconds();
+            director.start_wave(config, run_seconds, ship.score);
+        }
+        return;
+    }
+
+    if director.remaining == 0 {
+        let cleared = incoming.is_empty();
+        if cleared || director.elapsed >= config.max_duration {
+            // give a beaten up ship more time to recover
+            let mercy = 1. + (config.mercy - 1.).max(0.) * (1. - hp.fraction());
+            director.cooldown = config.rest * mercy;
+            director.resting = true;
+        }
+        return;
+    }
+
+    if director.cooldown > 0. {
+        return;
+    }
+
+    let wave = director.wave;
+    let available: Vec<_> = archetypes
+        .loaded(&definitions)
+        .into_iter()
+        .filter(|(_, archetype)| archetype.first_wave <= wave)
+        .collect();
+
+    let weights = WeightedIndex::new(available.iter().map(|(_, archetype)| archetype.weight));
+    let (handle, archetype) = match weights {
+        Ok(weights) => available[weights.sample(&mut rng.0)],
+        Err(_) => return,
+    };
+
+    spawn_incoming_asteroid(
+        &mut commands,
+        &mut rng.0,
+        handle,
+        archetype,
+        (player_ship.0, ship_transform),
+        director.speed,
+    );
+
+    director.remaining -= 1;
+    director.cooldown = director.interval;
+}
@@ -0,0 +1,315 @@
+use bevy::asset::{AssetPath, LoadContext};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::asteroids::AtlasSprite;
+use crate::basics::{Hitpoints, Lifetime, Mass, MaximumDistanceFrom, Velocity};
+use crate::collider::Collider;
+use crate::controls::Aim;
+use crate::damage::{DeathEvent, Resistances};
+use crate::director::Director;
+use crate::ron_asset::RonAsset;
+use crate::simulation::{LoadingAssets, SimulationRng, SimulationTime};
+use crate::weapons::{spawn_weapon, AimTarget, WeaponDefinition, WeaponProgress, WeaponSystem};
+use crate::{CleanupAfterGame, Heading, HitableByLaser, PlayerSpaceship, Spaceship};
+
+/// Kind of enemy ship, loaded from a `.enemy.ron` file in the `enemies` folder.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "b3f1c9d2-6e4a-4f0b-8d27-1a5c7e9f3b60"]
+pub struct EnemyArchetype {
+    /// How likely the director picks this archetype relative to the others.
+    pub weight: f32,
+    /// First wave the director sends this archetype in.
+    #[serde(default)]
+    pub first_wave: u32,
+    pub hitpoints: u32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    pub collider: Collider,
+    pub mass: f32,
+    /// Velocity gained per second while steering.
+    pub acceleration: f32,
+    pub max_speed: f32,
+    /// Distance the ship keeps from its target.
+    pub preferred_distance: f32,
+    /// Speed of circling around the target.
+    pub strafe_speed: f32,
+    /// Steers away from other enemy ships closer than this.
+    #[serde(default)]
+    pub separation: f32,
+    /// Opens fire once the target is closer than this.
+    pub fire_range: f32,
+    /// Paths of the weapons in slot order, only the first one gets fired.
+    pub weapons: Vec<String>,
+    /// Score for destroying the ship.
+    pub score: u32,
+    pub sprite: AtlasSprite,
+}
+
+impl RonAsset for EnemyArchetype {
+    const EXTENSIONS: &'static [&'static str] = &["enemy.ron"];
+
+    fn load_textures(&mut self, load_context: &mut LoadContext) -> Vec<AssetPath<'static>> {
+        self.sprite.load_atlas(load_context)
+    }
+}
+
+/// All the enemy archetypes, sorted by path so the director picks them deterministically.
+pub struct EnemyArchetypes(Vec<Handle<EnemyArchetype>>);
+
+impl FromWorld for EnemyArchetypes {
+    fn from_world(world: &mut World) -> Self {
+        EnemyArchetypes(LoadingAssets::load_folder(world, "enemies"))
+    }
+}
+
+impl EnemyArchetypes {
+    /// The archetypes already loaded, in path order.
+    pub fn loaded<'a>(
+        &'a self,
+        definitions: &'a Assets<EnemyArchetype>,
+    ) -> Vec<(&'a Handle<EnemyArchetype>, &'a EnemyArchetype)> {
+        self.0
+            .iter()
+            .filter_map(|handle| Some((handle, definitions.get(handle)?)))
+            .collect()
+    }
+}
+
+/// Enemy ship, steered by [`enemy_ai`] with the numbers of its archetype.
+///
+/// Not a [`Spaceship`], the systems of those fly them by the player's input and the game config,
+/// and their score only counts for the player.
+#[derive(Component)]
+pub struct Enemy {
+    pub archetype: Handle<EnemyArchetype>,
+    /// Direction of circling around the target, 1 counter-clockwise and -1 clockwise.
+    pub strafe: f32,
+}
+
+/// Spawns an enemy ship of `archetype` chasing `anchor`, its weapons pick up the `progress`
+/// of a saved run.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    handle: &Handle<EnemyArchetype>,
+    archetype: &EnemyArchetype,
+    weapons: &Assets<WeaponDefinition>,
+    asset_server: &AssetServer,
+    transform: Transform,
+    strafe: f32,
+    anchor: Entity,
+    progress: &[WeaponProgress],
+) -> Entity {
+    let weapon_handles: Vec<Handle<WeaponDefinition>> = archetype
+        .weapons
+        .iter()
+        .map(|path| asset_server.load(path.as_str()))
+        .collect();
+
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: archetype.sprite.atlas.clone(),
+            transform,
+            ..default()
+        })
+        .insert(CleanupAfterGame)
+        .insert(Enemy {
+            archetype: handle.clone(),
+            strafe,
+        })
+        .insert(Hitpoints::new(archetype.hitpoints))
+        .insert(archetype.resistances)
+        .insert(Heading::default())
+        .insert(Velocity::default())
+        .insert(Mass(archetype.mass))
+        .insert(archetype.collider.clone())
+        .insert(HitableByLaser::default())
+        .insert(WeaponSystem::automatic(weapon_handles.len()))
+        .insert(AimTarget::default())
+        .insert(MaximumDistanceFrom {
+            anchor,
+            distance: 2500.0,
+        })
+        .with_children(|parent| {
+            let system = parent.parent_entity();
+            for (slot, weapon) in weapon_handles.iter().enumerate() {
+                spawn_weapon(parent, weapons, weapon, slot, system, progress.get(slot));
+            }
+        })
+        .id()
+}
+
+/// Spawns the enemy ships of the current wave on a ring around the player's ship.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_wave_enemies(
+    mut commands: Commands,
+    mut director: ResMut<Director>,
+    player_ship: Res<PlayerSpaceship>,
+    ships: Query<&Transform, With<Spaceship>>,
+    archetypes: Res<EnemyArchetypes>,
+    definitions: Res<Assets<EnemyArchetype>>,
+    weapons: Res<Assets<WeaponDefinition>>,
+    asset_server: Res<AssetServer>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    if director.enemies == 0 {
+        return;
+    }
+
+    let ship = match ships.get(player_ship.0) {
+        Ok(ship) => ship,
+        Err(_) => return,
+    };
+
+    let available: Vec<_> = archetypes
+        .loaded(&definitions)
+        .into_iter()
+        .filter(|(_, archetype)| archetype.first_wave <= director.wave)
+        .collect();
+
+    let weights = WeightedIndex::new(available.iter().map(|(_, archetype)| archetype.weight));
+    let weights = match weights {
+        Ok(weights) => weights,
+        Err(_) => {
+            warn!("no enemy archetype available for wave {}", director.wave);
+            director.enemies = 0;
+            return;
+        }
+    };
+
+    for _ in 0..director.enemies {
+        let (handle, archetype) = available[weights.sample(&mut rng.0)];
+        let angle = rng.0.gen::<f32>() * std::f32::consts::TAU;
+        let position = ship.translation + Quat::from_rotation_z(angle) * Vec3::X * 1000.;
+        let strafe = if rng.0.gen_bool(0.5) { 1. } else { -1. };
+
+        spawn_enemy(
+            &mut commands,
+            handle,
+            archetype,
+            &weapons,
+            &asset_server,
+            Transform::from_translation(position),
+            strafe,
+            player_ship.0,
+            &[],
+        );
+    }
+
+    director.enemies = 0;
+}
+
+/// Steers enemy ships around the player's ship and fires at it when in range.
+#[allow(clippy::type_complexity)]
+pub fn enemy_ai(
+    mut enemies: Query<(
+        &mut Enemy,
+        &Transform,
+        &mut Heading,
+        &mut Velocity,
+        &mut WeaponSystem,
+        &mut AimTarget,
+        &mut TextureAtlasSprite,
+    )>,
+    player_ship: Res<PlayerSpaceship>,
+    ships: Query<&Transform, With<Spaceship>>,
+    definitions: Res<Assets<EnemyArchetype>>,
+    time: Res<SimulationTime>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    let dt = time.delta_seconds();
+    let target = match ships.get(player_ship.0) {
+        Ok(ship) => ship.translation,
+        Err(_) => return,
+    };
+
+    let positions: Vec<Vec3> = enemies.iter().map(|(_, t, ..)| t.translation).collect();
+
+    for (mut enemy, transform, mut heading, mut velocity, mut weapons, mut aim, mut sprite) in
+        enemies.iter_mut()
+    {
+        let archetype = match definitions.get(&enemy.archetype) {
+            Some(archetype) => archetype,
+            None => continue,
+        };
+
+        let mut offset = target - transform.translation;
+        offset.z = 0.;
+        let distance = offset.length();
+        let dir = offset.normalize_or_zero();
+
+        // seek the target until at the preferred distance, back off when closer
+        let approach = ((distance - archetype.preferred_distance) / archetype.preferred_distance)
+            .clamp(-1., 1.)
+            * archetype.max_speed;
+
+        // switch sides every few seconds on average
+        if rng.0.gen::<f32>() < dt / 4. {
+            enemy.strafe = -enemy.strafe;
+        }
+        let strafe = dir.cross(Vec3::Z) * -enemy.strafe * archetype.strafe_speed;
+
+        // push away from wingmen, stronger the closer they are
+        let mut separation = Vec3::ZERO;
+        for other in positions.iter() {
+            let mut away = transform.translation - *other;
+            away.z = 0.;
+            let gap = away.length();
+            if gap > 0. && gap < archetype.separation {
+                separation += away / gap * (1. - gap / archetype.separation);
+            }
+        }
+        let separation = separation * archetype.max_speed;
+
+        let desired = (dir * approach + strafe + separation).clamp_length_max(archetype.max_speed);
+        let steering = (desired - velocity.0).clamp_length_max(archetype.acceleration * dt);
+        velocity.0 += steering;
+
+        if dir != Vec3::ZERO {
+            heading.turn_towards(Heading::angle_of(dir), 0., 0., dt);
+        }
+        sprite.index = heading.sprite_index(archetype.sprite.columns * archetype.sprite.rows);
+
+        aim.0 = Aim::Position(target);
+        weapons.is_firing = distance <= archetype.fire_range;
+    }
+}
+
+/// Blows up destroyed enemy ships and credits their killer.
+pub fn enemy_destroyed(
+    mut cmd: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    enemies: Query<&Enemy>,
+    definitions: Res<Assets<EnemyArchetype>>,
+    mut ships: Query<&mut Spaceship>,
+) {
+    for death in deaths.iter() {
+        let enemy = match enemies.get(death.entity) {
+            Ok(enemy) => enemy,
+            Err(_) => continue,
+        };
+
+        if let Some(archetype) = definitions.get(&enemy.archetype) {
+            if let Some(mut ship) = death.killer.and_then(|killer| ships.get_mut(killer).ok()) {
+                ship.score += archetype.score;
+            }
+        }
+
+        // weapons go down with the ship
+        cmd.entity(death.entity).despawn_descendants();
+        cmd.entity(death.entity)
+            .remove_bundle::<(
+                Enemy,
+                Velocity,
+                Collider,
+                HitableByLaser,
+                Hitpoints,
+                WeaponSystem,
+            )>()
+            .insert(Lifetime::millis(200));
+    }
+}
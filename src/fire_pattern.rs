@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// How a projectile weapon releases its projectiles, in the `pattern` of its definition.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct FirePattern {
+    /// Projectiles of a single shot, fanned out evenly.
+    pub count: u32,
+    /// Angle (in radians) between the outermost projectiles of a shot.
+    pub spread: f32,
+    /// Shots fired one after another each time the weapon is ready.
+    pub burst: u32,
+    /// Seconds between two shots of a burst.
+    pub burst_delay: f32,
+    /// Maximum angle (in radians) each projectile randomly deviates by.
+    pub inaccuracy: f32,
+    /// Shots are held back while firing and released charged, fired right away when `None`.
+    pub charge: Option<Charge>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct Charge {
+    /// Seconds of holding until fully charged.
+    pub time: f32,
+    /// Damage multiplier of a fully charged shot, shorter holds scale down to 1.
+    pub max_multiplier: f32,
+}
+
+impl Default for FirePattern {
+    fn default() -> Self {
+        FirePattern {
+            count: 1,
+            spread: 0.,
+            burst: 1,
+            burst_delay: 0.,
+            inaccuracy: 0.,
+            charge: None,
+        }
+    }
+}
+
+impl FirePattern {
+    /// Directions of the projectiles of a single shot aimed along `aim`.
+    pub fn directions(&self, aim: Vec3, rng: &mut impl Rng) -> Vec<Vec3> {
+        let count = self.count.max(1);
+
+        (0..count)
+            .map(|i| {
+                let mut angle = match count {
+                    1 => 0.,
+                    _ => self.spread * (i as f32 / (count - 1) as f32 - 0.5),
+                };
+                if self.inaccuracy > 0. {
+                    angle += rng.gen_range(-self.inaccuracy..self.inaccuracy);
+                }
+                Quat::from_rotation_z(angle) * aim
+            })
+            .collect()
+    }
+}
+
+/// State of the trigger of a weapon for a single tick.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct TriggerInput {
+    /// The trigger is held down.
+    pub pulled: bool,
+    /// The weapon is the current one of its system, switching away drops its charge.
+    pub selected: bool,
+    /// Fully charged shots go off on their own, for controllers that never let go of the trigger.
+    pub auto_release: bool,
+}
+
+/// Progress of a weapon through its [`FirePattern`].
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Trigger {
+    /// Shots of the current burst still to fire.
+    burst_left: u32,
+    /// Seconds until the next shot of the burst.
+    burst_cooldown: f32,
+    /// Damage multiplier of the current burst.
+    multiplier: f32,
+    /// Seconds the weapon has been charging.
+    charged: f32,
+}
+
+impl Trigger {
+    /// Damage multiplier of the shot to fire this tick, `None` when not firing.
+    ///
+    /// A burst starts and resets `cooldown` once it's finished, the rest of the burst fires even
+    /// when the weapon stops firing. Charged shots go off when the trigger is let go of.
+    pub fn update(
+        &mut self,
+        pattern: &FirePattern,
+        input: TriggerInput,
+        cooldown: &mut Timer,
+        dt: f32,
+    ) -> Option<f32> {
+        if self.burst_left > 0 {
+            self.burst_cooldown -= dt;
+            if self.burst_cooldown > 0. {
+                return None;
+            }
+            self.burst_left -= 1;
+            self.burst_cooldown = pattern.burst_delay;
+            return Some(self.multiplier);
+        }
+
+        if !input.selected {
+            self.charged = 0.;
+        }
+
+        if !cooldown.finished() {
+            return None;
+        }
+
+        let firing = input.pulled && input.selected;
+        self.multiplier = match pattern.charge {
+            Some(charge) if firing => {
+                self.charged = (self.charged + dt).min(charge.time);
+                if !input.auto_release || self.charged < charge.time {
+                    return None;
+                }
+                self.charged = 0.;
+                charge.max_multiplier
+            }
+            Some(charge) if self.charged > 0. => {
+                let charged = self.charged / charge.time.max(f32::EPSILON);
+                self.charged = 0.;
+                1. + (charge.max_multiplier - 1.) * charged.min(1.)
+            }
+            None if firing => 1.,
+            _ => return None,
+        };
+
+        cooldown.reset();
+        self.burst_left = pattern.burst.max(1) - 1;
+        self.burst_cooldown = pattern.burst_delay;
+        Some(self.multiplier)
+    }
+}
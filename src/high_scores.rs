@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::basics::Hitpoints;
+use crate::level_generation::LevelGenerator;
+use crate::simulation::TIMESTEP;
+use crate::{AppState, PlayerSpaceship, Spaceship};
+
+/// Number of runs shown in the high-score table.
+pub const TOP_RUNS: usize = 10;
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load_or_default())
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(record_run));
+    }
+}
+
+/// Statistics of the current run.
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+pub struct RunStats {
+    pub ticks: u64,
+    pub asteroids_destroyed: u32,
+    pub shards_collected: u32,
+}
+
+pub fn count_run_ticks(mut stats: ResMut<RunStats>) {
+    stats.ticks += 1;
+}
+
+/// A finished run.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunRecord {
+    pub score: u32,
+    pub seed: u64,
+    /// Length of the run in seconds.
+    pub duration: f32,
+    pub asteroids_destroyed: u32,
+    pub shards_collected: u32,
+    /// When the run ended, in seconds since the unix epoch.
+    pub finished_at: u64,
+}
+
+/// Every finished run, stored in the user's data directory.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct HighScores {
+    runs: Vec<RunRecord>,
+}
+
+impl HighScores {
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("asteroids").join("high_scores.ron"))
+    }
+
+    pub fn load_or_default() -> Self {
+        let path = match HighScores::path() {
+            Some(path) => path,
+            None => return HighScores::default(),
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => match ron::from_str(&content) {
+                Ok(scores) => scores,
+                Err(e) => {
+                    eprintln!("not able to parse {}: {}", path.display(), e);
+                    HighScores::default()
+                }
+            },
+            Err(_) => HighScores::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let path = match HighScores::path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let content = ron::ser::to_string_pretty(self, Default::default())
+            .expect("high scores are always serializable");
+
+        let result = std::fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| std::fs::write(&path, content));
+
+        if let Err(e) = result {
+            eprintln!("not able to save {}: {}", path.display(), e);
+        }
+    }
+
+    /// All runs in the order they were played.
+    pub fn history(&self) -> &[RunRecord] {
+        &self.runs
+    }
+
+    /// Indices (into [`HighScores::history`]) of the best runs, best first.
+    pub fn top(&self) -> Vec<usize> {
+        let mut best: Vec<usize> = (0..self.runs.len()).collect();
+        // earlier runs win ties, the record has to be beaten to change hands
+        best.sort_by_key(|&i| std::cmp::Reverse(self.runs[i].score));
+        best.truncate(TOP_RUNS);
+        best
+    }
+
+    /// Adds a finished run and returns its index.
+    pub fn add(&mut self, run: RunRecord) -> usize {
+        self.runs.push(run);
+        self.runs.len() - 1
+    }
+}
+
+/// The run that just ended, highlighted in the menu.
+pub struct LastRun {
+    pub index: usize,
+    /// The run beat all previous ones.
+    pub new_record: bool,
+}
+
+fn record_run(
+    mut cmd: Commands,
+    player_ship: Res<PlayerSpaceship>,
+    ships: Query<(&Spaceship, &Hitpoints)>,
+    stats: Res<RunStats>,
+    generator: Res<LevelGenerator>,
+    mut scores: ResMut<HighScores>,
+) {
+    let score = match ships.get(player_ship.0) {
+        // the run is only over once the ship is destroyed, otherwise it can be continued
+        Ok((ship, hp)) if hp.is_dead() => ship.score,
+        _ => return,
+    };
+
+    let new_record = score > 0 && scores.history().iter().all(|run| score > run.score);
+
+    let finished_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+
+    let index = scores.add(RunRecord {
+        score,
+        seed: generator.seed(),
+        duration: stats.ticks as f32 * TIMESTEP,
+        asteroids_destroyed: stats.asteroids_destroyed,
+        shards_collected: stats.shards_collected,
+        finished_at,
+    });
+    scores.save();
+
+    cmd.insert_resource(LastRun { index, new_record });
+}
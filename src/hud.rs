@@ -1,4 +1,5 @@
 use super::*;
+use crate::bosses::{Boss, BossArchetype, BossPart};
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -7,6 +8,16 @@ pub struct Healthbar;
 #[derive(Component)]
 pub struct Score;
 
+#[derive(Component)]
+pub struct WaveNumber;
+
+/// Hidden while no boss is around.
+#[derive(Component)]
+pub struct BossHealthbarFrame;
+
+#[derive(Component)]
+pub struct BossHealthbar;
+
 pub struct UiMaterials {
     font: Handle<Font>,
     health_bar: Handle<Image>,
@@ -93,6 +104,63 @@ pub fn init_hud(mut cmd: Commands, materials: Res<UiMaterials>) {
                     });
             });
 
+        parent
+            .spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "",
+                    TextStyle {
+                        font: materials.font.clone(),
+                        font_size: 28.0,
+                        color: Color::WHITE,
+                    },
+                    TextAlignment::default(),
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        top: Val::Px(16.0),
+                        left: Val::Px(16.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(WaveNumber)
+            .insert(CleanupAfterGame);
+
+        parent
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        top: Val::Px(16.0),
+                        left: Val::Percent(30.0),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(40.0), Val::Px(12.0)),
+                    ..default()
+                },
+                color: Color::rgba_u8(0, 0, 0, 160).into(),
+                ..default()
+            })
+            .insert(BossHealthbarFrame)
+            .insert(CleanupAfterGame)
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                            ..default()
+                        },
+                        color: Color::rgb_u8(230, 120, 30).into(),
+                        ..default()
+                    })
+                    .insert(BossHealthbar)
+                    .insert(CleanupAfterGame);
+            });
+
         // parent
         //     .spawn_bundle(TextBundle {
         //         text: Text::with_section("Score:", default(), default()),
@@ -164,7 +232,7 @@ pub fn hud_healthbar(
 ) {
     if let Ok((ship, hp)) = ships.get(player_ship.0) {
         for mut style in healthbar.iter_mut() {
-            style.max_size.width = Val::Percent(hp.0 as f32);
+            style.max_size.width = Val::Percent(hp.fraction() * 100.);
         }
 
         for mut text in score.iter_mut() {
@@ -172,3 +240,43 @@ pub fn hud_healthbar(
         }
     }
 }
+
+pub fn hud_wave(director: Res<Director>, mut labels: Query<&mut Text, With<WaveNumber>>) {
+    for mut text in labels.iter_mut() {
+        text.sections[0].value = match director.wave {
+            0 => String::new(),
+            wave => format!("Wave {}", wave),
+        };
+    }
+}
+
+/// Shows the hitpoints left over all the parts of the boss.
+#[allow(clippy::type_complexity)]
+pub fn hud_boss_healthbar(
+    bosses: Query<(Entity, &Boss)>,
+    parts: Query<(&BossPart, &Hitpoints)>,
+    definitions: Res<Assets<BossArchetype>>,
+    mut frames: Query<&mut Style, (With<BossHealthbarFrame>, Without<BossHealthbar>)>,
+    mut healthbar: Query<&mut Style, With<BossHealthbar>>,
+) {
+    let health = bosses.iter().next().and_then(|(entity, boss)| {
+        let total = definitions.get(&boss.archetype)?.total_hitpoints();
+        let left: u32 = parts
+            .iter()
+            .filter(|(part, _)| part.boss == entity)
+            .map(|(_, hp)| hp.current)
+            .sum();
+        Some(left as f32 / total.max(1) as f32)
+    });
+
+    for mut style in frames.iter_mut() {
+        style.display = match health {
+            Some(_) => Display::Flex,
+            None => Display::None,
+        };
+    }
+
+    for mut style in healthbar.iter_mut() {
+        style.size.width = Val::Percent(health.unwrap_or(0.) * 100.);
+    }
+}
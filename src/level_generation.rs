@@ -3,16 +3,33 @@ use std::hash::Hash;
 use super::*;
 use bevy::prelude::*;
 use bevy::utils::HashSet;
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::{rngs::SmallRng, seq::SliceRandom as _};
+use serde::{Deserialize, Serialize};
 
 const CHUNK_SIZE: f32 = 3000.0;
 
+/// Chunks along the side of a region sharing a single density value, chunks in between blend them.
+const DENSITY_REGION: i32 = 4;
+
+/// Most asteroid fields a chunk of the densest region gets.
+const MAX_FIELDS: f32 = 3.0;
+
+/// Fields keep this far away from the origin, where every run starts.
+const SAFE_RADIUS: f32 = 800.0;
+
+/// Mixed into the seeds, so the fields don't repeat the numbers used for the background.
+const DENSITY_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+const FIELDS_SALT: u64 = 0xc2b2_ae3d_27d4_eb4f;
+
 pub struct LevelGenerator {
     world_seed: u64,
     generated_chunks: HashSet<Chunk>,
+    /// Field asteroids already destroyed, they stay gone when their chunk gets generated again.
+    destroyed: HashSet<(Chunk, FieldAsteroid)>,
 }
 
-#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Component, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Chunk(i32, i32);
 
 impl Chunk {
@@ -42,18 +59,69 @@ impl Chunk {
     }
 }
 
+/// Asteroid placed by the fields of its [`Chunk`], numbered in the order they're generated in.
+#[derive(
+    Component, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct FieldAsteroid(u32);
+
 impl LevelGenerator {
     pub fn new(world_seed: u64) -> Self {
         LevelGenerator {
             world_seed,
             generated_chunks: Default::default(),
+            destroyed: Default::default(),
+        }
+    }
+
+    /// Generator of `world_seed` with `chunks` already generated again and the `destroyed`
+    /// field asteroids remembered.
+    pub fn restore(
+        world_seed: u64,
+        chunks: Vec<Chunk>,
+        destroyed: Vec<(Chunk, FieldAsteroid)>,
+        cmd: &mut Commands,
+        materials: &GameMaterials,
+    ) -> Self {
+        let mut generator = LevelGenerator::new(world_seed);
+        for chunk in chunks {
+            generator.generate_chunk(chunk, cmd, materials);
         }
+        generator.destroyed.extend(destroyed);
+        generator
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.world_seed
+    }
+
+    pub fn generated_chunks(&self) -> Vec<Chunk> {
+        let mut chunks: Vec<_> = self.generated_chunks.iter().cloned().collect();
+        chunks.sort();
+        chunks
     }
 
-    fn generate_chunk(&mut self, chunk: Chunk, cmd: &mut Commands, materials: &GameMaterials) {
+    pub fn destroyed_asteroids(&self) -> Vec<(Chunk, FieldAsteroid)> {
+        let mut destroyed: Vec<_> = self.destroyed.iter().cloned().collect();
+        destroyed.sort();
+        destroyed
+    }
+
+    /// Keeps `asteroid` of `chunk` from coming back, so fields can't be farmed for score.
+    pub fn asteroid_destroyed(&mut self, chunk: &Chunk, asteroid: FieldAsteroid) {
+        self.destroyed.insert((chunk.clone(), asteroid));
+    }
+
+    /// Places the background of `chunk`, returns `false` when it already was generated.
+    fn generate_chunk(
+        &mut self,
+        chunk: Chunk,
+        cmd: &mut Commands,
+        materials: &GameMaterials,
+    ) -> bool {
         let mut chunk_rng = SmallRng::seed_from_u64(chunk.seed() ^ self.world_seed);
         if self.generated_chunks.contains(&chunk) {
-            return;
+            return false;
         }
 
         let offset = Vec2::new(
@@ -93,23 +161,154 @@ impl LevelGenerator {
         }
 
         self.generated_chunks.insert(chunk);
+        true
+    }
+
+    /// How crowded `chunk` is with asteroids, from 0 in empty space to 1 in the densest regions.
+    fn density(&self, chunk: &Chunk) -> f32 {
+        let corner = |x: i32, y: i32| {
+            let seed = Chunk(x, y).seed() ^ self.world_seed ^ DENSITY_SALT;
+            SmallRng::seed_from_u64(seed).gen::<f32>()
+        };
+
+        let (x, y) = (
+            chunk.0.div_euclid(DENSITY_REGION),
+            chunk.1.div_euclid(DENSITY_REGION),
+        );
+        let (tx, ty) = (
+            chunk.0.rem_euclid(DENSITY_REGION) as f32 / DENSITY_REGION as f32,
+            chunk.1.rem_euclid(DENSITY_REGION) as f32 / DENSITY_REGION as f32,
+        );
+
+        let bottom = corner(x, y) + (corner(x + 1, y) - corner(x, y)) * tx;
+        let top = corner(x, y + 1) + (corner(x + 1, y + 1) - corner(x, y + 1)) * tx;
+        let density = bottom + (top - bottom) * ty;
+
+        // leave most of the world fairly empty
+        density * density
+    }
+
+    /// Places the asteroid clusters and belts of `chunk`, always the same ones for the same seed.
+    ///
+    /// Asteroids overlapping one placed before are left out, as are the ones already destroyed.
+    fn generate_asteroid_fields(
+        &self,
+        chunk: &Chunk,
+        cmd: &mut Commands,
+        archetypes: &[(&Handle<AsteroidArchetype>, &AsteroidArchetype)],
+    ) {
+        let weights = match WeightedIndex::new(archetypes.iter().map(|(_, a)| a.weight)) {
+            Ok(weights) => weights,
+            Err(_) => return,
+        };
+
+        let mut rng = SmallRng::seed_from_u64(chunk.seed() ^ self.world_seed ^ FIELDS_SALT);
+        let density = self.density(chunk);
+        let offset = Vec2::new(
+            (chunk.0 as f32 * CHUNK_SIZE) - (CHUNK_SIZE * 0.5),
+            (chunk.1 as f32 * CHUNK_SIZE) - (CHUNK_SIZE * 0.5),
+        );
+
+        let mut placed: Vec<(Vec2, f32)> = Vec::new();
+        let mut index = 0;
+
+        let fields = (density * MAX_FIELDS).round() as usize;
+        for _ in 0..fields {
+            let center = offset + Vec2::new(rng.gen::<f32>(), rng.gen::<f32>()) * CHUNK_SIZE;
+            let count = 6 + (density * 14.0) as usize;
+
+            let positions: Vec<Vec2> = if rng.gen_bool(0.5) {
+                // cluster
+                let radius = rng.gen_range(200.0..500.0);
+                (0..count)
+                    .map(|_| {
+                        let angle = rng.gen_range(0.0..TAU);
+                        let distance = radius * rng.gen::<f32>().sqrt();
+                        center + Vec2::new(angle.cos(), angle.sin()) * distance
+                    })
+                    .collect()
+            } else {
+                // belt
+                let angle = rng.gen_range(0.0..TAU);
+                let dir = Vec2::new(angle.cos(), angle.sin());
+                let length = rng.gen_range(1500.0..3000.0);
+                let width = rng.gen_range(100.0..250.0);
+                (0..count * 2)
+                    .map(|_| {
+                        let along = rng.gen_range(-0.5..0.5) * length;
+                        let across = rng.gen_range(-0.5..0.5) * width;
+                        center + dir * along + dir.perp() * across
+                    })
+                    .collect()
+            };
+
+            for position in positions {
+                if position.length() < SAFE_RADIUS {
+                    continue;
+                }
+
+                let (handle, archetype) = archetypes[weights.sample(&mut rng)];
+                let drift = Vec2::new(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0));
+                let spin = archetype.random_spin(&mut rng);
+
+                let asteroid = FieldAsteroid(index);
+                index += 1;
+
+                let (min, max) = archetype
+                    .collider
+                    .shape(&GlobalTransform::from_scale(Vec3::splat(archetype.scale)))
+                    .bounds();
+                let radius = (max - min).max_element() * 0.5;
+                let overlaps = placed
+                    .iter()
+                    .any(|(other, other_radius)| position.distance(*other) < radius + other_radius);
+                if overlaps {
+                    continue;
+                }
+                placed.push((position, radius));
+
+                if self.destroyed.contains(&(chunk.clone(), asteroid)) {
+                    continue;
+                }
+
+                let entity = spawn_asteroid(
+                    cmd,
+                    handle,
+                    archetype,
+                    Transform::from_translation(position.extend(0.5)),
+                    drift.extend(0.),
+                    spin,
+                    None,
+                );
+                cmd.entity(entity).insert(chunk.clone()).insert(asteroid);
+            }
+        }
     }
 }
 
+/// Generates the chunks around explorers, with their background and asteroid fields.
 pub fn generate_background(
     mut cmd: Commands,
     materials: Res<GameMaterials>,
     mut generator: ResMut<LevelGenerator>,
     explorers: Query<&GlobalTransform, With<ChunkExplorer>>,
-    mut chunk_to_generate: Local<HashSet<Chunk>>,
+    archetypes: Res<AsteroidArchetypes>,
+    definitions: Res<Assets<AsteroidArchetype>>,
 ) {
-    for explorer in explorers.iter() {
-        let chunks = Chunk::chunk_with_surrounding(explorer.translation);
-        chunk_to_generate.extend(chunks);
-    }
+    let mut chunk_to_generate: Vec<Chunk> = explorers
+        .iter()
+        .flat_map(|explorer| Chunk::chunk_with_surrounding(explorer.translation))
+        .collect();
+
+    // spawning in a fixed order keeps the simulation deterministic
+    chunk_to_generate.sort();
+    chunk_to_generate.dedup();
 
-    for chunk in chunk_to_generate.drain() {
-        generator.generate_chunk(chunk, &mut cmd, &materials);
+    let archetypes = archetypes.loaded(&definitions);
+    for chunk in chunk_to_generate {
+        if generator.generate_chunk(chunk.clone(), &mut cmd, &materials) {
+            generator.generate_asteroid_fields(&chunk, &mut cmd, &archetypes);
+        }
     }
 }
 
@@ -124,8 +323,8 @@ impl Default for CleanupTimer {
 pub fn cleanup_chunks(
     mut cmd: Commands,
     mut timer: Local<CleanupTimer>,
-    time: Res<Time>,
-    generator: Res<LevelGenerator>,
+    time: Res<SimulationTime>,
+    mut generator: ResMut<LevelGenerator>,
     chunk_entities: Query<(Entity, &Chunk)>,
     explorers: Query<&GlobalTransform, With<ChunkExplorer>>,
 ) {
@@ -139,6 +338,7 @@ pub fn cleanup_chunks(
                     .iter()
                     .all(|explorer| explorer.translation.distance_squared(pos) > (9000.0 * 9000.0))
             })
+            .cloned()
             .collect();
 
         for (entity, chunk) in chunk_entities.iter() {
@@ -146,6 +346,11 @@ pub fn cleanup_chunks(
                 cmd.entity(entity).despawn();
             }
         }
+
+        // generate them again when an explorer comes back
+        for chunk in chunk_to_cleanup {
+            generator.generated_chunks.remove(&chunk);
+        }
     }
 }
 
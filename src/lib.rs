@@ -0,0 +1,468 @@
+pub mod asteroids;
+pub mod basics;
+pub mod bosses;
+pub mod camera;
+pub mod collider;
+pub mod collision;
+pub mod config;
+pub mod controls;
+pub mod damage;
+pub mod director;
+pub mod enemies;
+pub mod fire_pattern;
+pub mod high_scores;
+pub mod hud;
+pub mod level_generation;
+pub mod magnet;
+pub mod math;
+pub mod menu;
+pub mod replay;
+pub mod ron_asset;
+pub mod save;
+pub mod simulation;
+pub mod spatial;
+pub mod weapons;
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+use bevy::render::camera::Camera;
+
+use asteroids::*;
+use basics::*;
+use bosses::BossPart;
+use camera::*;
+use collider::Collider;
+use collision::CollisionStarted;
+use config::{FlightModel, GameConfig};
+use controls::{ControlsPlugin, PlayerInput};
+use damage::{DamageEvent, DamageKind, DeathEvent, HealEvent};
+use director::Director;
+use enemies::Enemy;
+use high_scores::RunStats;
+use hud::*;
+use level_generation::*;
+use magnet::magnets;
+use rand::{Rng as _, SeedableRng};
+use replay::ReplayRecorder;
+use simulation::*;
+use weapons::*;
+
+pub const APP_STATE_STAGE: &str = "app_state_stage";
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub enum AppState {
+    Loading,
+    Menu,
+    InGame,
+}
+
+pub fn game_app() -> App {
+    let mut app = App::new();
+
+    app.add_plugins(DefaultPlugins)
+        .insert_resource(ClearColor(Color::rgb_u8(0, 20, 24)))
+        // .add_resource(Msaa { samples: 1 })
+        .add_startup_system(setup)
+        .init_resource::<UiMaterials>()
+        .add_plugin(ControlsPlugin)
+        .add_plugin(GameSimulationPlugin {
+            after_loading: AppState::Menu,
+        })
+        .add_plugin(menu::MenuPlugin)
+        .add_plugin(save::SavePlugin)
+        .add_plugin(high_scores::HighScorePlugin)
+        .add_system_set(SystemSet::on_enter(AppState::InGame).with_system(init_hud))
+        .add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(sprite_animation)
+                .with_system(mouse_position)
+                .with_system(camera::camera_follow)
+                .with_system(laser_impact)
+                .with_system(hud_healthbar)
+                .with_system(hud_wave)
+                .with_system(hud_boss_healthbar), // .with_system(update_score),
+        );
+
+    app
+}
+
+pub fn run_headless(mut app: App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+
+        // a replay pauses the simulation once it runs out of input
+        if app.world.resource::<SimulationTime>().paused {
+            break;
+        }
+    }
+
+    let world = &mut app.world;
+    if let Some(mut recorder) = world.get_resource_mut::<ReplayRecorder>() {
+        recorder.finish();
+    }
+
+    let tick = world.resource::<SimulationTime>().tick;
+    let player = world.resource::<PlayerSpaceship>().0;
+    match world.query::<(&Spaceship, &Hitpoints)>().get(world, player) {
+        Ok((ship, hp)) => println!("tick {}: score {}, hp {}", tick, ship.score, hp.current),
+        Err(_) => println!("tick {}: ship destroyed", tick),
+    }
+}
+
+pub struct GameMaterials {
+    spaceship2: Handle<TextureAtlas>,
+    laser_impact: Handle<TextureAtlas>,
+    nebulas: Vec<Handle<Image>>,
+    star: Handle<Image>,
+    // ship: Handle<ColorMaterial>,
+}
+
+impl FromWorld for GameMaterials {
+    fn from_world(world: &mut World) -> Self {
+        if !world.contains_resource::<Assets<TextureAtlas>>() {
+            // running without rendering, there is nothing to load textures with
+            return GameMaterials::placeholder();
+        }
+
+        let world = world.cell();
+        let asset_server = world.get_resource_mut::<AssetServer>().unwrap();
+        let mut texture_atlases = world.get_resource_mut::<Assets<TextureAtlas>>().unwrap();
+
+        let laser_impact = asset_server.load("laser_impact.png");
+        let laser_impact = TextureAtlas::from_grid(laser_impact, Vec2::new(8.0, 8.0), 4, 1);
+
+        let spaceship2 = asset_server.load("spaceship2.png");
+        let spaceship2 = TextureAtlas::from_grid(spaceship2, Vec2::new(32.0, 32.0), 8, 1);
+
+        GameMaterials {
+            spaceship2: texture_atlases.add(spaceship2),
+            laser_impact: texture_atlases.add(laser_impact),
+            nebulas: vec![asset_server.load("nebula.png")],
+            star: asset_server.load("star.png"),
+            // ship: materials.add(asset_server.load("spaceship.png").into()),
+        }
+    }
+}
+
+impl GameMaterials {
+    fn placeholder() -> Self {
+        GameMaterials {
+            spaceship2: Handle::default(),
+            laser_impact: Handle::default(),
+            nebulas: vec![Handle::default()],
+            star: Handle::default(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Spaceship {
+    score: u32,
+}
+
+/// Direction the ship faces, in radians counter-clockwise from up.
+#[derive(Component, Default)]
+pub struct Heading {
+    angle: f32,
+    /// Radians per second.
+    turn_speed: f32,
+}
+
+impl Heading {
+    fn dir(&self) -> Vec3 {
+        Quat::from_rotation_z(self.angle) * Vec3::Y
+    }
+
+    /// Angle of the heading facing `dir`.
+    fn angle_of(dir: Vec3) -> f32 {
+        dir.angle_between(Vec3::Y) * -dir.x.signum()
+    }
+
+    /// Frame of a sprite sheet with `frames` directions, starting up and going counter-clockwise.
+    fn sprite_index(&self, frames: usize) -> usize {
+        (frames as f32 * self.angle / TAU).round() as usize % frames
+    }
+
+    /// Turns towards `target` without exceeding the given acceleration and speed,
+    /// slowing down in time to stop facing it.
+    fn turn_towards(&mut self, target: f32, acceleration: f32, max_speed: f32, dt: f32) {
+        if acceleration <= 0. {
+            self.angle = target.rem_euclid(TAU);
+            self.turn_speed = 0.;
+            return;
+        }
+
+        let remaining = (target - self.angle + PI).rem_euclid(TAU) - PI;
+        let desired =
+            remaining.signum() * max_speed.min((2. * acceleration * remaining.abs()).sqrt());
+
+        let change = (desired - self.turn_speed).clamp(-acceleration * dt, acceleration * dt);
+        self.turn_speed += change;
+        self.angle = (self.angle + self.turn_speed * dt).rem_euclid(TAU);
+    }
+}
+
+#[derive(Default)]
+pub struct MouseWorldPos(Vec3);
+
+#[derive(Component)]
+pub struct HitableByLaser {
+    damage_tick: Timer,
+    /// Damage dealt by the beam currently hitting.
+    damage: u32,
+    kind: DamageKind,
+    /// Ship firing the beam currently hitting.
+    source: Option<Entity>,
+}
+
+impl Default for HitableByLaser {
+    fn default() -> Self {
+        HitableByLaser {
+            damage_tick: Timer::from_seconds(0.15, true),
+            damage: 0,
+            kind: DamageKind::Energy,
+            source: None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct CleanupAfterGame;
+
+pub struct PlayerSpaceship(Entity);
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    if let Err(e) = asset_server.watch_for_changes() {
+        eprintln!("not able to enable hot-reloading: {}", e);
+    }
+
+    commands.spawn_bundle(UiCameraBundle::default());
+
+    let main_camera = commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .id();
+
+    commands.insert_resource(MainCamera(main_camera));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_game(
+    mut cmd: Commands,
+    sprites: Res<GameMaterials>,
+    generator: Res<LevelGenerator>,
+    chunks: Query<Entity, With<Chunk>>,
+    definitions: Res<WeaponDefinitions>,
+    weapons: Res<Assets<WeaponDefinition>>,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    cmd.insert_resource(SimulationRng::new(generator.seed()));
+    cmd.insert_resource(RunStats::default());
+    cmd.insert_resource(Director::new(&config.waves));
+
+    // every run generates the world from scratch, so it plays out the same way
+    for entity in chunks.iter() {
+        cmd.entity(entity).despawn();
+    }
+    cmd.insert_resource(LevelGenerator::new(generator.seed()));
+
+    let loadout = definitions.loadout(&asset_server, &config.ship.weapons);
+
+    let spaceship = cmd
+        // .spawn(SpriteBundle {
+        //     material: sprites.ship.clone(),
+        //     ..default()
+        // })
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: sprites.spaceship2.clone(),
+            ..default()
+        })
+        .insert(Spaceship { score: 0 })
+        .insert(Hitpoints::new(config.ship.hitpoints))
+        .insert(config.ship.resistances)
+        .insert(Heading::default())
+        .insert(Velocity::default())
+        .insert(CleanupAfterGame)
+        .insert(ChunkExplorer)
+        .insert(config.ship.collider.clone())
+        .insert(Mass(config.ship.mass))
+        .insert(HitableByLaser::default())
+        .insert(WeaponSystem::new(loadout.len()))
+        .insert(AimTarget::default())
+        .insert(config.ship.magnet())
+        .with_children(|ship| {
+            let system = ship.parent_entity();
+            for (slot, weapon) in loadout.iter().enumerate() {
+                spawn_weapon(ship, &weapons, weapon, slot, system, None);
+            }
+        })
+        .id();
+
+    cmd.insert_resource(PlayerSpaceship(spaceship));
+
+    // STATIC TEST ASTERIOD
+    // commands
+    //     .spawn(SpriteSheetBundle {
+    //         texture_atlas: sprites.asteroid.clone(),
+    //         transform: Transform::from_translation(Vec3::new(150., 150., 0.)),
+    //         ..default()
+    //     })
+    //     .with(CleanupAfterGame)
+    //     .with(Asteroid)
+    //     .with(Hitpoints(3))
+    //     .with(HitableByLaser {
+    //         damage_tick: Timer::from_seconds(1., false),
+    //     });
+}
+
+fn mouse_position(
+    main_camera: Res<MainCamera>,
+    windows: Res<Windows>,
+    camera_query: Query<&Transform, With<Camera>>,
+    mut cursor_moved_reader: EventReader<CursorMoved>,
+    mut mouse_world_pos: ResMut<MouseWorldPos>,
+    mut last_mouse_event: Local<Option<CursorMoved>>,
+) {
+    if let Some(last_event) = cursor_moved_reader.iter().last() {
+        *last_mouse_event = Some(last_event.clone());
+    }
+
+    if let Some(event) = last_mouse_event.as_ref() {
+        use bevy::math::Vec4Swizzles as _;
+        let camera_transform = camera_query.get(main_camera.0).unwrap();
+        let window = windows.get(event.id).unwrap();
+        let window_size = Vec2::new(window.width(), window.height());
+        let p = event.position - window_size * 0.5;
+
+        mouse_world_pos.0 = (camera_transform.compute_matrix() * p.extend(0.0).extend(1.0)).xyz();
+    }
+}
+
+fn ship_movement(
+    input: Res<PlayerInput>,
+    mut ships: Query<
+        (
+            &Transform,
+            &mut Heading,
+            &mut Velocity,
+            &mut TextureAtlasSprite,
+        ),
+        With<Spaceship>,
+    >,
+    time: Res<SimulationTime>,
+    config: Res<GameConfig>,
+) {
+    let ship = &config.ship;
+    let dt = time.delta_seconds();
+    for (transform, mut heading, mut velocity, mut sprite) in ships.iter_mut() {
+        // there is no direction to face when aiming right at the ship, keep the heading
+        let aim = input.aim.dir_from(transform.translation);
+        if aim != Vec3::ZERO {
+            let target = Heading::angle_of(aim);
+            heading.turn_towards(target, ship.turn_acceleration, ship.max_turn_speed, dt);
+        }
+        sprite.index = heading.sprite_index(8);
+
+        let acceleration = if input.thrust_to_aim {
+            heading.dir()
+        } else {
+            input.thrust.extend(0.)
+        };
+
+        if acceleration.length_squared() > 0. {
+            velocity.0 += acceleration.clamp_length_max(1.) * ship.speed_gain * dt;
+        } else if ship.flight_model == FlightModel::Assisted {
+            let speed = velocity.0.length();
+            velocity.0 = velocity.0.normalize_or_zero() * (speed - ship.speed_gain * dt).max(0.);
+        }
+
+        velocity.0 *= (1. - ship.drag * dt).max(0.);
+        velocity.0 = velocity.0.clamp_length_max(ship.max_speed);
+    }
+}
+
+/// Ends the run once the player's ship is destroyed.
+fn game_over(
+    player_ship: Res<PlayerSpaceship>,
+    mut deaths: EventReader<DeathEvent>,
+    mut states: ResMut<State<AppState>>,
+) {
+    if deaths.iter().any(|death| death.entity == player_ship.0) {
+        // more ticks can run before the state actually changes
+        let _ = states.overwrite_replace(AppState::Menu);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn bullets_hit(
+    mut cmd: Commands,
+    mut collisions: EventReader<CollisionStarted>,
+    targets: Query<(), With<Hitpoints>>,
+    mut bullets: Query<&mut Bullet>,
+    enemies: Query<(), Or<(With<Enemy>, With<BossPart>)>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for collision in collisions.iter() {
+        let pair = collision.matching(|e| bullets.get(e).is_ok(), |e| targets.get(e).is_ok());
+        let (bullet_entity, target) = match pair {
+            Some(pair) => pair,
+            None => continue,
+        };
+
+        let mut bullet = bullets.get_mut(bullet_entity).unwrap();
+        // bullets leave through the ship that fired them, enemies don't shoot each other down
+        let friendly = enemies.get(bullet.source).is_ok() && enemies.get(target).is_ok();
+        if !bullet.already_hit && bullet.source != target && !friendly {
+            bullet.already_hit = true;
+            damage.send(DamageEvent {
+                target,
+                source: Some(bullet.source),
+                amount: bullet.damage,
+                kind: bullet.kind,
+            });
+            cmd.entity(bullet_entity).despawn();
+        }
+    }
+}
+
+pub fn laser_beams_hit(
+    mut hitables: Query<(Entity, &mut HitableByLaser), With<Hitpoints>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for (entity, mut hitable) in hitables.iter_mut() {
+        if hitable.damage_tick.just_finished() {
+            hitable.damage_tick.reset();
+            damage.send(DamageEvent {
+                target: entity,
+                source: hitable.source,
+                amount: hitable.damage,
+                kind: hitable.kind,
+            });
+        }
+    }
+}
+
+fn ship_eats_shards(
+    mut cmd: Commands,
+    mut collisions: EventReader<CollisionStarted>,
+    mut ships: Query<&mut Spaceship>,
+    shards: Query<(), With<Shard>>,
+    config: Res<GameConfig>,
+    mut stats: ResMut<RunStats>,
+    mut heal: EventWriter<HealEvent>,
+) {
+    for collision in collisions.iter() {
+        let pair = collision.matching(|e| ships.get(e).is_ok(), |e| shards.get(e).is_ok());
+        if let Some((ship, shard)) = pair {
+            ships.get_mut(ship).unwrap().score += 10;
+            stats.shards_collected += 1;
+            if config.ship.shard_healing > 0 {
+                heal.send(HealEvent {
+                    target: ship,
+                    amount: config.ship.shard_healing,
+                });
+            }
+            cmd.entity(shard).despawn()
+        }
+    }
+}
@@ -1,6 +1,10 @@
+use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 
 use crate::basics::{Lifetime, Velocity};
+use crate::simulation::SimulationTime;
+use crate::spatial::SpatialIndex;
 
 #[derive(Component)]
 pub struct MagnetAttractable;
@@ -11,43 +15,60 @@ pub struct Magnet {
     pub max_distance: f32,
 }
 
+/// Pulls attractable items towards the closest magnet in range.
+///
+/// Runs before the spatial index gets rebuilt, so it finds the items where the previous tick left them.
+/// Only items with a collider are found.
 pub fn magnets(
     mut attracted_items: Query<
         (&Transform, &mut Velocity, Option<&mut Lifetime>),
         With<MagnetAttractable>,
     >,
-    magntes: Query<(&Transform, &Magnet), Without<MagnetAttractable>>,
-    time: Res<Time>,
+    magntes: Query<(Entity, &Transform, &Magnet), Without<MagnetAttractable>>,
+    index: Res<SpatialIndex>,
+    time: Res<SimulationTime>,
 ) {
-    for (item_transform, mut item_velocity, item_lifetime) in attracted_items.iter_mut() {
-        let closest_magnet = magntes
-            .iter()
-            .filter(|(t, magnet)| {
-                t.translation.distance_squared(item_transform.translation)
-                    < magnet.max_distance.powi(2)
-            })
-            .min_by(|(m1, _), (m2, _)| {
-                f32::total_cmp(
-                    &m1.translation.distance_squared(item_transform.translation),
-                    &m2.translation.distance_squared(item_transform.translation),
-                )
-            });
-
-        if let Some((magnet_transform, magnet)) = closest_magnet {
-            use bevy::math::Vec3Swizzles;
-
-            let magnet_pos = magnet_transform.translation.xy();
-            let item_pos = item_transform.translation.xy();
-
-            let force_direction = (magnet_pos - item_pos).normalize();
-
-            let force = force_direction * (magnet.force * 100.) * time.delta_seconds();
-
-            item_velocity.0 = force.extend(0.);
-
-            if let Some(mut item_lifetime) = item_lifetime {
-                item_lifetime.prevent_tick = true;
+    let mut closest_magnets: HashMap<Entity, (f32, Entity)> = HashMap::default();
+
+    for (magnet_entity, magnet_transform, magnet) in magntes.iter() {
+        let magnet_pos = magnet_transform.translation.xy();
+
+        for item in index.query_circle(magnet_pos, magnet.max_distance) {
+            let item_transform = match attracted_items.get(item) {
+                Ok((transform, _, _)) => transform,
+                Err(_) => continue,
+            };
+
+            let distance = item_transform.translation.xy().distance_squared(magnet_pos);
+            if distance >= magnet.max_distance.powi(2) {
+                continue;
             }
+
+            let closest = closest_magnets
+                .entry(item)
+                .or_insert((distance, magnet_entity));
+            if distance < closest.0 {
+                *closest = (distance, magnet_entity);
+            }
+        }
+    }
+
+    for (item, (_, magnet_entity)) in closest_magnets {
+        let (_, magnet_transform, magnet) = magntes.get(magnet_entity).unwrap();
+        let (item_transform, mut item_velocity, item_lifetime) =
+            attracted_items.get_mut(item).unwrap();
+
+        let magnet_pos = magnet_transform.translation.xy();
+        let item_pos = item_transform.translation.xy();
+
+        let force_direction = (magnet_pos - item_pos).normalize();
+
+        let force = force_direction * (magnet.force * 100.) * time.delta_seconds();
+
+        item_velocity.0 = force.extend(0.);
+
+        if let Some(mut item_lifetime) = item_lifetime {
+            item_lifetime.prevent_tick = true;
         }
     }
 }
@@ -13,3 +13,18 @@ pub fn ray_circle_intersection(start: Vec2, dir: Vec2, origin: Vec2, radius: f32
     let thc = (radius * radius - d2).sqrt();
     Some(start + dir * (tca - thc))
 }
+
+/// Distance along the ray where it crosses the segment from `a` to `b`.
+pub fn ray_segment_intersection(start: Vec2, dir: Vec2, a: Vec2, b: Vec2) -> Option<f32> {
+    let edge = b - a;
+    let denominator = dir.perp_dot(edge);
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let to_a = a - start;
+    let t = to_a.perp_dot(edge) / denominator;
+    let u = to_a.perp_dot(dir) / denominator;
+
+    (t >= 0. && (0. ..=1.).contains(&u)).then_some(t)
+}
@@ -1,5 +1,9 @@
 use bevy::{prelude::*, ui::FocusPolicy};
 
+use crate::controls::{Action, ActionState};
+use crate::high_scores::{HighScores, LastRun};
+use crate::replay::ReplayRecorder;
+use crate::save::{ResumeRun, SavedRun};
 use crate::AppState;
 
 pub struct MenuPlugin;
@@ -8,7 +12,12 @@ impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ButtonMaterials>()
             .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(enter))
-            .add_system_set(SystemSet::on_update(AppState::Menu).with_system(update))
+            .add_system_set(
+                SystemSet::on_update(AppState::Menu)
+                    .with_system(update)
+                    .with_system(continue_run)
+                    .with_system(confirm),
+            )
             .add_system_set(SystemSet::on_exit(AppState::Menu).with_system(exit));
     }
 }
@@ -19,7 +28,11 @@ struct MenuUi;
 #[derive(Component)]
 struct StartGameButton;
 
+#[derive(Component)]
+struct ContinueButton;
+
 struct ButtonMaterials {
+    font: Handle<Font>,
     play: Handle<Image>,
     play_hover: Handle<Image>,
 }
@@ -30,13 +43,20 @@ impl FromWorld for ButtonMaterials {
         let asset_server = world.get_resource_mut::<AssetServer>().unwrap();
 
         ButtonMaterials {
+            font: asset_server.load("ui/AGENCYB.ttf"),
             play: asset_server.load("ui/button.png"),
             play_hover: asset_server.load("ui/button_outline.png"),
         }
     }
 }
 
-fn enter(mut commands: Commands, button_colors: Res<ButtonMaterials>) {
+fn enter(
+    mut commands: Commands,
+    button_colors: Res<ButtonMaterials>,
+    scores: Res<HighScores>,
+    last_run: Option<Res<LastRun>>,
+    recorder: Option<Res<ReplayRecorder>>,
+) {
     commands
         .spawn_bundle(NodeBundle {
             focus_policy: FocusPolicy::Pass,
@@ -44,6 +64,7 @@ fn enter(mut commands: Commands, button_colors: Res<ButtonMaterials>) {
                 size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::ColumnReverse,
                 ..default()
             },
             color: Color::NONE.into(),
@@ -63,9 +84,114 @@ fn enter(mut commands: Commands, button_colors: Res<ButtonMaterials>) {
                     ..default()
                 })
                 .insert(StartGameButton);
+
+            // a replay starts from a fresh run, it couldn't play back a continued one
+            if SavedRun::exists() && recorder.is_none() {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(400.0), Val::Px(100.0)),
+                            margin: Rect {
+                                top: Val::Px(20.0),
+                                ..default()
+                            },
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        image: button_colors.play.clone().into(),
+                        ..default()
+                    })
+                    .insert(ContinueButton)
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                "Continue",
+                                TextStyle {
+                                    font: button_colors.font.clone(),
+                                    font_size: 48.0,
+                                    color: Color::WHITE,
+                                },
+                                TextAlignment::default(),
+                            ),
+                            focus_policy: FocusPolicy::Pass,
+                            ..default()
+                        });
+                    });
+            }
+
+            let text_style = |color| TextStyle {
+                font: button_colors.font.clone(),
+                font_size: 28.0,
+                color,
+            };
+
+            let title = match last_run.as_deref() {
+                Some(LastRun {
+                    new_record: true, ..
+                }) => "New record!",
+                _ => "High scores",
+            };
+
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        margin: Rect {
+                            top: Val::Px(40.0),
+                            ..default()
+                        },
+                        align_items: AlignItems::Center,
+                        flex_direction: FlexDirection::ColumnReverse,
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            title,
+                            text_style(Color::WHITE),
+                            TextAlignment::default(),
+                        ),
+                        focus_policy: FocusPolicy::Pass,
+                        ..default()
+                    });
+
+                    for (rank, index) in scores.top().into_iter().enumerate() {
+                        let run = &scores.history()[index];
+                        let color = match last_run.as_deref() {
+                            Some(last_run) if last_run.index == index => {
+                                Color::rgb_u8(255, 200, 60)
+                            }
+                            _ => Color::rgb_u8(180, 180, 180),
+                        };
+
+                        let row = format!(
+                            "{}. {}  ({:.0} s, {} asteroids, {} shards)",
+                            rank + 1,
+                            run.score,
+                            run.duration,
+                            run.asteroids_destroyed,
+                            run.shards_collected,
+                        );
+
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                row,
+                                text_style(color),
+                                TextAlignment::default(),
+                            ),
+                            focus_policy: FocusPolicy::Pass,
+                            ..default()
+                        });
+                    }
+                });
         });
 }
 
+#[allow(clippy::type_complexity)]
 fn update(
     button_colors: Res<ButtonMaterials>,
     mut interaction_query: Query<
@@ -89,8 +215,45 @@ fn update(
     }
 }
 
+#[allow(clippy::type_complexity)]
+fn continue_run(
+    mut cmd: Commands,
+    button_colors: Res<ButtonMaterials>,
+    mut interaction_query: Query<
+        (&Interaction, &mut UiImage),
+        (Changed<Interaction>, With<ContinueButton>),
+    >,
+    mut states: ResMut<State<AppState>>,
+) {
+    for (interaction, mut image) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => match SavedRun::load() {
+                Some(run) => {
+                    cmd.insert_resource(ResumeRun(run));
+                    states.replace(AppState::InGame).unwrap();
+                }
+                None => error!("not able to continue the saved run"),
+            },
+            Interaction::Hovered => {
+                *image = button_colors.play_hover.clone().into();
+            }
+            Interaction::None => {
+                *image = button_colors.play.clone().into();
+            }
+        }
+    }
+}
+
+fn confirm(mut actions: ResMut<ActionState>, mut states: ResMut<State<AppState>>) {
+    if actions.take_presses(Action::Confirm) > 0 {
+        let _ = states.overwrite_replace(AppState::InGame);
+    }
+}
+
 fn exit(mut commands: Commands, query: Query<Entity, With<MenuUi>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
+
+    commands.remove_resource::<LastRun>();
 }
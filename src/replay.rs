@@ -0,0 +1,301 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use crate::config::GameConfig;
+use crate::controls::{Aim, PlayerInput};
+use crate::level_generation::LevelGenerator;
+use crate::simulation::SimulationTime;
+
+// Replay file layout, all numbers are little endian:
+//
+//   header: magic "ASTR", version u16, world seed u64, config length u32, config RON
+//   frames: repeat u16, thrust x/y f32, aim x/y f32, flags u8, switch weapon i8
+//
+// Each frame is played for `repeat` consecutive ticks, so idle stretches stay small.
+const MAGIC: &[u8; 4] = b"ASTR";
+const VERSION: u16 = 3;
+
+const FLAG_FIRING: u8 = 1 << 0;
+const FLAG_THRUST_TO_AIM: u8 = 1 << 1;
+const FLAG_AIM_DIRECTION: u8 = 1 << 2;
+
+/// Input of a whole run, enough to play it again exactly as it happened.
+#[derive(Clone)]
+pub struct Replay {
+    pub seed: u64,
+    /// The config the run was played with.
+    pub config: GameConfig,
+    frames: Vec<(u16, PlayerInput)>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 4];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a replay file",
+            ));
+        }
+
+        let version = u16::from_le_bytes(read_bytes(&mut file)?);
+        if version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported replay version {}", version),
+            ));
+        }
+
+        let seed = u64::from_le_bytes(read_bytes(&mut file)?);
+
+        let length = u32::from_le_bytes(read_bytes(&mut file)?);
+        let mut config = vec![0; length as usize];
+        file.read_exact(&mut config)?;
+        let config = ron::de::from_bytes(&config)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut frames = Vec::new();
+        loop {
+            let repeat = match read_bytes(&mut file) {
+                Ok(bytes) => u16::from_le_bytes(bytes),
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            };
+
+            let thrust_x = f32::from_le_bytes(read_bytes(&mut file)?);
+            let thrust_y = f32::from_le_bytes(read_bytes(&mut file)?);
+            let aim_x = f32::from_le_bytes(read_bytes(&mut file)?);
+            let aim_y = f32::from_le_bytes(read_bytes(&mut file)?);
+            let [flags, switch_weapon] = read_bytes(&mut file)?;
+
+            let aim = if flags & FLAG_AIM_DIRECTION != 0 {
+                Aim::Direction(Vec2::new(aim_x, aim_y))
+            } else {
+                Aim::Position(Vec3::new(aim_x, aim_y, 0.))
+            };
+
+            let input = PlayerInput {
+                thrust: Vec2::new(thrust_x, thrust_y),
+                thrust_to_aim: flags & FLAG_THRUST_TO_AIM != 0,
+                aim,
+                firing: flags & FLAG_FIRING != 0,
+                switch_weapon: switch_weapon as i8,
+            };
+
+            frames.push((repeat, input));
+        }
+
+        Ok(Replay {
+            seed,
+            config,
+            frames,
+        })
+    }
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+struct ReplayWriter {
+    file: BufWriter<File>,
+    last: PlayerInput,
+    repeat: u16,
+}
+
+impl ReplayWriter {
+    fn create(path: &Path, seed: u64, config: &GameConfig) -> io::Result<Self> {
+        let config =
+            ron::to_string(config).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&VERSION.to_le_bytes())?;
+        file.write_all(&seed.to_le_bytes())?;
+        file.write_all(&(config.len() as u32).to_le_bytes())?;
+        file.write_all(config.as_bytes())?;
+
+        Ok(ReplayWriter {
+            file,
+            last: PlayerInput::default(),
+            repeat: 0,
+        })
+    }
+
+    fn push(&mut self, input: &PlayerInput) -> io::Result<()> {
+        if self.repeat > 0 && self.repeat < u16::MAX && self.last == *input {
+            self.repeat += 1;
+            return Ok(());
+        }
+
+        self.write_frame()?;
+        self.last = *input;
+        self.repeat = 1;
+        Ok(())
+    }
+
+    fn write_frame(&mut self) -> io::Result<()> {
+        if self.repeat == 0 {
+            return Ok(());
+        }
+
+        let input = &self.last;
+        let mut flags = 0;
+        if input.firing {
+            flags |= FLAG_FIRING;
+        }
+        if input.thrust_to_aim {
+            flags |= FLAG_THRUST_TO_AIM;
+        }
+
+        let aim = match input.aim {
+            Aim::Position(position) => position.truncate(),
+            Aim::Direction(direction) => {
+                flags |= FLAG_AIM_DIRECTION;
+                direction
+            }
+        };
+
+        self.file.write_all(&self.repeat.to_le_bytes())?;
+        self.file.write_all(&input.thrust.x.to_le_bytes())?;
+        self.file.write_all(&input.thrust.y.to_le_bytes())?;
+        self.file.write_all(&aim.x.to_le_bytes())?;
+        self.file.write_all(&aim.y.to_le_bytes())?;
+        self.file.write_all(&[flags, input.switch_weapon as u8])
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.write_frame()?;
+        self.file.flush()
+    }
+}
+
+/// Records every run into a replay file at `path`, overwriting the previous run.
+pub struct ReplayRecorder {
+    path: PathBuf,
+    writer: Option<ReplayWriter>,
+}
+
+impl ReplayRecorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        ReplayRecorder {
+            path: path.into(),
+            writer: None,
+        }
+    }
+
+    pub fn finish(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            match writer.finish() {
+                Ok(()) => info!("replay saved to {}", self.path.display()),
+                Err(e) => error!("not able to save replay: {}", e),
+            }
+        }
+    }
+}
+
+/// Feeds recorded input to the game instead of the input devices.
+pub struct ReplayPlayer {
+    replay: Replay,
+    frame: usize,
+    repeated: u16,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer {
+            replay,
+            frame: 0,
+            repeated: 0,
+        }
+    }
+
+    fn next(&mut self) -> Option<PlayerInput> {
+        let (repeat, input) = self.replay.frames.get(self.frame)?;
+
+        self.repeated += 1;
+        if self.repeated == *repeat {
+            self.frame += 1;
+            self.repeated = 0;
+        }
+
+        Some(*input)
+    }
+}
+
+/// Starts recording or playing a run, a played run gets the config it was recorded with.
+pub fn start_replay(
+    recorder: Option<ResMut<ReplayRecorder>>,
+    player: Option<ResMut<ReplayPlayer>>,
+    generator: Res<LevelGenerator>,
+    mut config: ResMut<GameConfig>,
+) {
+    if let Some(player) = player.as_ref() {
+        *config = player.replay.config.clone();
+    }
+
+    if let Some(mut recorder) = recorder {
+        match ReplayWriter::create(&recorder.path, generator.seed(), &config) {
+            Ok(writer) => recorder.writer = Some(writer),
+            Err(e) => error!("not able to record replay: {}", e),
+        }
+    }
+
+    if let Some(mut player) = player {
+        player.frame = 0;
+        player.repeated = 0;
+    }
+}
+
+/// Overrides the input with the replay, pauses the simulation once the replay runs out.
+pub fn play_replay(
+    player: Option<ResMut<ReplayPlayer>>,
+    mut input: ResMut<PlayerInput>,
+    mut time: ResMut<SimulationTime>,
+) {
+    if let Some(mut player) = player {
+        match player.next() {
+            Some(next) => *input = next,
+            None => {
+                info!("replay finished at tick {}", time.tick);
+                *input = PlayerInput::default();
+                time.paused = true;
+            }
+        }
+    }
+}
+
+pub fn record_replay(recorder: Option<ResMut<ReplayRecorder>>, input: Res<PlayerInput>) {
+    if let Some(mut recorder) = recorder {
+        if let Some(writer) = recorder.writer.as_mut() {
+            if let Err(e) = writer.push(&input) {
+                error!("not able to record replay: {}", e);
+                recorder.writer = None;
+            }
+        }
+    }
+}
+
+pub fn finish_replay(recorder: Option<ResMut<ReplayRecorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.finish();
+    }
+}
+
+pub fn finish_replay_on_exit(
+    exit_reader: EventReader<AppExit>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+) {
+    if !exit_reader.is_empty() {
+        finish_replay(recorder);
+    }
+}
@@ -0,0 +1,80 @@
+use std::marker::PhantomData;
+
+use bevy::asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::de::DeserializeOwned;
+
+/// Asset that designers write by hand as a RON file.
+pub trait RonAsset: DeserializeOwned + TypeUuid + Send + Sync + 'static {
+    const EXTENSIONS: &'static [&'static str];
+
+    /// Resolves handles of the textures the asset refers to and returns their paths.
+    ///
+    /// Only called when rendering, otherwise the handles are left at their defaults.
+    fn load_textures(&mut self, _load_context: &mut LoadContext) -> Vec<AssetPath<'static>> {
+        Vec::new()
+    }
+}
+
+pub struct RonAssetPlugin<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for RonAssetPlugin<T> {
+    fn default() -> Self {
+        RonAssetPlugin(PhantomData)
+    }
+}
+
+impl<T: RonAsset> Plugin for RonAssetPlugin<T> {
+    fn build(&self, app: &mut App) {
+        // without rendering there is nothing able to load the textures
+        let load_textures = app.world.contains_resource::<Assets<Image>>();
+
+        app.add_asset::<T>().add_asset_loader(RonAssetLoader::<T> {
+            load_textures,
+            marker: PhantomData,
+        });
+    }
+}
+
+struct RonAssetLoader<T> {
+    load_textures: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: RonAsset> AssetLoader for RonAssetLoader<T> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut asset: T = ron::de::from_bytes(bytes)?;
+
+            let mut textures = Vec::new();
+            if self.load_textures {
+                textures = asset.load_textures(load_context);
+            }
+
+            load_context.set_default_asset(LoadedAsset::new(asset).with_dependencies(textures));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        T::EXTENSIONS
+    }
+}
+
+/// Handle to a texture referenced by `path` from within a RON asset.
+pub fn texture_handle(
+    load_context: &LoadContext,
+    path: &str,
+    dependencies: &mut Vec<AssetPath<'static>>,
+) -> Handle<Image> {
+    let path = AssetPath::new(path.into(), None);
+    let handle = load_context.get_handle(path.get_id());
+    dependencies.push(path);
+    handle
+}
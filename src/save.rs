@@ -0,0 +1,793 @@
+use std::path::PathBuf;
+
+use bevy::asset::Asset;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::asteroids::{spawn_asteroid, spawn_shard, Asteroid, AsteroidArchetype, Shard};
+use crate::basics::{Hitpoints, Lifetime, Rotation, Velocity};
+use crate::bosses::{spawn_boss, spawn_boss_part, Boss, BossArchetype, BossPart};
+use crate::controls::{Action, ActionState};
+use crate::damage::DamageKind;
+use crate::director::Director;
+use crate::enemies::{spawn_enemy, Enemy, EnemyArchetype};
+use crate::high_scores::RunStats;
+use crate::level_generation::{Chunk, FieldAsteroid, LevelGenerator};
+use crate::replay::ReplayPlayer;
+use crate::simulation::{SimulationRng, SimulationTime};
+use crate::weapons::{
+    spawn_bullet, spawn_missile, Bullet, Missile, WeaponCannon, WeaponDefinition,
+    WeaponMissileLauncher, WeaponProgress, WeaponSlot, WeaponSystem,
+};
+use crate::{AppState, GameMaterials, Heading, PlayerSpaceship, Spaceship};
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(save_on_pause)
+                .with_system(save_and_quit)
+                .with_system(restore_run),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(discard_finished_run));
+    }
+}
+
+/// Run in progress, stored in the user's data directory so it can be continued later.
+#[derive(Serialize, Deserialize)]
+pub struct SavedRun {
+    seed: u64,
+    chunks: Vec<Chunk>,
+    /// Field asteroids destroyed so far.
+    #[serde(default)]
+    destroyed: Vec<(Chunk, FieldAsteroid)>,
+    tick: u64,
+    #[serde(default)]
+    stats: RunStats,
+    #[serde(default)]
+    director: Director,
+    /// State of the simulation's randomness, runs saved without it get reseeded.
+    #[serde(default)]
+    rng: Option<SimulationRng>,
+    ship: SavedShip,
+    asteroids: Vec<SavedAsteroid>,
+    shards: Vec<SavedShard>,
+    #[serde(default)]
+    enemies: Vec<SavedEnemy>,
+    #[serde(default)]
+    bosses: Vec<SavedBoss>,
+    #[serde(default)]
+    bullets: Vec<SavedProjectile>,
+    #[serde(default)]
+    missiles: Vec<SavedProjectile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedShip {
+    position: Vec3,
+    velocity: Vec3,
+    #[serde(default)]
+    heading: f32,
+    score: u32,
+    hitpoints: u32,
+    weapon: usize,
+    /// Cooldowns and triggers of the weapons in slot order.
+    #[serde(default)]
+    weapons: Vec<WeaponProgress>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedAsteroid {
+    /// Path of the archetype asset.
+    archetype: String,
+    position: Vec3,
+    #[serde(default)]
+    rotation: Quat,
+    velocity: Vec3,
+    #[serde(default)]
+    spin: f32,
+    hitpoints: u32,
+    /// Chunk of the asteroid field it belongs to, `None` for the ones flying at the ship.
+    #[serde(default)]
+    chunk: Option<Chunk>,
+    #[serde(default)]
+    field: Option<FieldAsteroid>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedShard {
+    archetype: String,
+    position: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    velocity: Vec3,
+    #[serde(default)]
+    spin: f32,
+    seconds_left: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedEnemy {
+    /// Path of the archetype asset.
+    archetype: String,
+    position: Vec3,
+    velocity: Vec3,
+    heading: f32,
+    strafe: f32,
+    hitpoints: u32,
+    weapons: Vec<WeaponProgress>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedBoss {
+    /// Path of the archetype asset.
+    archetype: String,
+    position: Vec3,
+    rotation: Quat,
+    /// Parts not destroyed yet, the phase follows from the ones missing.
+    parts: Vec<SavedBossPart>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedBossPart {
+    /// Index into the parts of the archetype.
+    index: usize,
+    hitpoints: u32,
+    weapons: Vec<WeaponProgress>,
+}
+
+/// Bullet or missile, the ones fired by ships no longer around aren't saved.
+#[derive(Serialize, Deserialize)]
+struct SavedProjectile {
+    /// Path of the weapon definition.
+    weapon: String,
+    position: Vec3,
+    rotation: Quat,
+    velocity: Vec3,
+    seconds_left: f32,
+    damage: u32,
+    kind: DamageKind,
+    source: SavedSource,
+}
+
+/// Ship that fired a projectile.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+enum SavedSource {
+    Player,
+    /// Index into the saved enemies.
+    Enemy(usize),
+    /// Index into the saved bosses and the index of the part.
+    BossPart(usize, usize),
+}
+
+impl SavedRun {
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("asteroids").join("run.ron"))
+    }
+
+    pub fn exists() -> bool {
+        SavedRun::path().is_some_and(|path| path.exists())
+    }
+
+    pub fn load() -> Option<Self> {
+        let path = SavedRun::path()?;
+        let content = std::fs::read_to_string(&path).ok()?;
+
+        match ron::from_str(&content) {
+            Ok(run) => Some(run),
+            Err(e) => {
+                error!("not able to parse {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let path = match SavedRun::path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let content = ron::ser::to_string_pretty(self, Default::default())
+            .expect("saved run is always serializable");
+
+        let result = std::fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| std::fs::write(&path, content));
+
+        match result {
+            Ok(()) => info!("run saved to {}", path.display()),
+            Err(e) => error!("not able to save {}: {}", path.display(), e),
+        }
+    }
+
+    /// Removes the saved run, so a finished run can't be continued.
+    pub fn delete() {
+        if let Some(path) = SavedRun::path() {
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    error!("not able to remove {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+}
+
+/// Run to restore when entering [`AppState::InGame`], inserted by the menu's "Continue" button.
+pub struct ResumeRun(pub SavedRun);
+
+/// Everything needed to take a snapshot of the current run.
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct RunSnapshot<'w, 's> {
+    asset_server: Res<'w, AssetServer>,
+    generator: Res<'w, LevelGenerator>,
+    sim_time: Res<'w, SimulationTime>,
+    stats: Res<'w, RunStats>,
+    director: Res<'w, Director>,
+    rng: Res<'w, SimulationRng>,
+    player_ship: Res<'w, PlayerSpaceship>,
+    ships: Query<
+        'w,
+        's,
+        (
+            &'static Spaceship,
+            &'static Hitpoints,
+            &'static Transform,
+            &'static Heading,
+            &'static Velocity,
+            &'static WeaponSystem,
+        ),
+    >,
+    asteroids: Query<
+        'w,
+        's,
+        (
+            &'static Asteroid,
+            &'static Hitpoints,
+            &'static Transform,
+            &'static Velocity,
+            &'static Rotation,
+            Option<&'static Chunk>,
+            Option<&'static FieldAsteroid>,
+        ),
+    >,
+    shards: Query<
+        'w,
+        's,
+        (
+            &'static Shard,
+            &'static Transform,
+            &'static Velocity,
+            &'static Rotation,
+            &'static Lifetime,
+        ),
+    >,
+    enemies: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Enemy,
+            &'static Hitpoints,
+            &'static Transform,
+            &'static Heading,
+            &'static Velocity,
+            &'static WeaponSystem,
+        ),
+    >,
+    bosses: Query<'w, 's, (Entity, &'static Boss, &'static Transform)>,
+    parts: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static BossPart,
+            &'static Hitpoints,
+            &'static WeaponSystem,
+        ),
+    >,
+    weapons: Query<
+        'w,
+        's,
+        (
+            &'static WeaponSlot,
+            Option<&'static WeaponCannon>,
+            Option<&'static WeaponMissileLauncher>,
+        ),
+    >,
+    projectiles: Query<
+        'w,
+        's,
+        (
+            Option<&'static Bullet>,
+            Option<&'static Missile>,
+            &'static Transform,
+            &'static Velocity,
+            &'static Lifetime,
+        ),
+        Or<(With<Bullet>, With<Missile>)>,
+    >,
+}
+
+impl RunSnapshot<'_, '_> {
+    fn asset_path<T: Asset>(&self, handle: &Handle<T>) -> Option<String> {
+        let path = self.asset_server.get_handle_path(handle)?;
+        Some(path.path().to_string_lossy().into_owned())
+    }
+
+    /// Cooldowns and triggers of the weapons of `system`, in slot order.
+    fn weapon_progress(&self, system: Entity, slots: usize) -> Vec<WeaponProgress> {
+        let mut progress = vec![WeaponProgress::default(); slots];
+        for (weapon_slot, cannon, launcher) in self.weapons.iter() {
+            if weapon_slot.system != system || weapon_slot.slot >= slots {
+                continue;
+            }
+
+            if let Some(cannon) = cannon {
+                progress[weapon_slot.slot] = cannon.progress();
+            } else if let Some(launcher) = launcher {
+                progress[weapon_slot.slot] = launcher.progress();
+            }
+        }
+        progress
+    }
+
+    /// Snapshot of the run, `None` when the player's ship is already destroyed.
+    pub fn take(&self) -> Option<SavedRun> {
+        let (ship, hp, transform, heading, velocity, weapons) =
+            self.ships.get(self.player_ship.0).ok()?;
+
+        let asteroids = self
+            .asteroids
+            .iter()
+            .filter_map(|(asteroid, hp, transform, velocity, spin, chunk, field)| {
+                Some(SavedAsteroid {
+                    archetype: self.asset_path(&asteroid.archetype)?,
+                    position: transform.translation,
+                    rotation: transform.rotation,
+                    velocity: velocity.0,
+                    spin: spin.0,
+                    hitpoints: hp.current,
+                    chunk: chunk.cloned(),
+                    field: field.copied(),
+                })
+            })
+            .collect();
+
+        let shards = self
+            .shards
+            .iter()
+            .filter_map(|(shard, transform, velocity, spin, lifetime)| {
+                Some(SavedShard {
+                    archetype: self.asset_path(&shard.archetype)?,
+                    position: transform.translation,
+                    rotation: transform.rotation,
+                    scale: transform.scale,
+                    velocity: velocity.0,
+                    spin: spin.0,
+                    seconds_left: lifetime.seconds_left(),
+                })
+            })
+            .collect();
+
+        // projectiles remember the ship that fired them by its place in the saved run
+        let mut sources = HashMap::default();
+        sources.insert(self.player_ship.0, SavedSource::Player);
+
+        let mut enemies = Vec::new();
+        for (entity, enemy, hp, transform, heading, velocity, weapons) in self.enemies.iter() {
+            let archetype = match self.asset_path(&enemy.archetype) {
+                Some(archetype) => archetype,
+                None => continue,
+            };
+
+            sources.insert(entity, SavedSource::Enemy(enemies.len()));
+            enemies.push(SavedEnemy {
+                archetype,
+                position: transform.translation,
+                velocity: velocity.0,
+                heading: heading.angle,
+                strafe: enemy.strafe,
+                hitpoints: hp.current,
+                weapons: self.weapon_progress(entity, weapons.slots),
+            });
+        }
+
+        let mut bosses = Vec::new();
+        for (entity, boss, transform) in self.bosses.iter() {
+            let archetype = match self.asset_path(&boss.archetype) {
+                Some(archetype) => archetype,
+                None => continue,
+            };
+
+            let mut parts: Vec<_> = self
+                .parts
+                .iter()
+                .filter(|(_, part, ..)| part.boss == entity)
+                .collect();
+            parts.sort_by_key(|(_, part, ..)| part.index);
+
+            for (part_entity, part, ..) in parts.iter() {
+                sources.insert(
+                    *part_entity,
+                    SavedSource::BossPart(bosses.len(), part.index),
+                );
+            }
+            bosses.push(SavedBoss {
+                archetype,
+                position: transform.translation,
+                rotation: transform.rotation,
+                parts: parts
+                    .into_iter()
+                    .map(|(part_entity, part, hp, weapons)| SavedBossPart {
+                        index: part.index,
+                        hitpoints: hp.current,
+                        weapons: self.weapon_progress(part_entity, weapons.slots),
+                    })
+                    .collect(),
+            });
+        }
+
+        let mut bullets = Vec::new();
+        let mut missiles = Vec::new();
+        for (bullet, missile, transform, velocity, lifetime) in self.projectiles.iter() {
+            let (definition, damage, kind, source, saved) = match (bullet, missile) {
+                (Some(bullet), _) => (
+                    &bullet.definition,
+                    bullet.damage,
+                    bullet.kind,
+                    bullet.source,
+                    &mut bullets,
+                ),
+                (_, Some(missile)) => (
+                    &missile.definition,
+                    missile.damage,
+                    missile.kind,
+                    missile.source,
+                    &mut missiles,
+                ),
+                _ => continue,
+            };
+            let (weapon, source) = match (self.asset_path(definition), sources.get(&source)) {
+                (Some(weapon), Some(source)) => (weapon, *source),
+                _ => continue,
+            };
+
+            saved.push(SavedProjectile {
+                weapon,
+                position: transform.translation,
+                rotation: transform.rotation,
+                velocity: velocity.0,
+                seconds_left: lifetime.seconds_left(),
+                damage,
+                kind,
+                source,
+            });
+        }
+
+        Some(SavedRun {
+            seed: self.generator.seed(),
+            chunks: self.generator.generated_chunks(),
+            destroyed: self.generator.destroyed_asteroids(),
+            tick: self.sim_time.tick,
+            stats: *self.stats,
+            director: self.director.clone(),
+            rng: Some(self.rng.clone()),
+            ship: SavedShip {
+                position: transform.translation,
+                velocity: velocity.0,
+                heading: heading.angle,
+                score: ship.score,
+                hitpoints: hp.current,
+                weapon: weapons.current,
+                weapons: self.weapon_progress(self.player_ship.0, weapons.slots),
+            },
+            asteroids,
+            shards,
+            enemies,
+            bosses,
+            bullets,
+            missiles,
+        })
+    }
+}
+
+/// Saves the run whenever it gets paused, a replay pausing at its end doesn't overwrite the save.
+fn save_on_pause(
+    sim_time: Res<SimulationTime>,
+    mut was_paused: Local<bool>,
+    replay: Option<Res<ReplayPlayer>>,
+    run: RunSnapshot,
+) {
+    if sim_time.paused && !*was_paused && replay.is_none() {
+        if let Some(run) = run.take() {
+            run.save();
+        }
+    }
+
+    *was_paused = sim_time.paused;
+}
+
+fn save_and_quit(
+    mut actions: ResMut<ActionState>,
+    mut states: ResMut<State<AppState>>,
+    replay: Option<Res<ReplayPlayer>>,
+    run: RunSnapshot,
+) {
+    if actions.take_presses(Action::Quit) > 0 {
+        if replay.is_none() {
+            if let Some(run) = run.take() {
+                run.save();
+            }
+        }
+
+        let _ = states.overwrite_replace(AppState::Menu);
+    }
+}
+
+/// Puts the saved run back in place of the freshly started one.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn restore_run(
+    mut cmd: Commands,
+    resume: Option<Res<ResumeRun>>,
+    player_ship: Res<PlayerSpaceship>,
+    mut ships: Query<(
+        &mut Spaceship,
+        &mut Hitpoints,
+        &mut Transform,
+        &mut Heading,
+        &mut Velocity,
+        &mut WeaponSystem,
+    )>,
+    mut weapons: Query<(
+        &WeaponSlot,
+        Option<&mut WeaponCannon>,
+        Option<&mut WeaponMissileLauncher>,
+    )>,
+    chunks: Query<Entity, With<Chunk>>,
+    materials: Res<GameMaterials>,
+    mut sim_time: ResMut<SimulationTime>,
+    asset_server: Res<AssetServer>,
+    archetypes: Res<Assets<AsteroidArchetype>>,
+    enemy_archetypes: Res<Assets<EnemyArchetype>>,
+    boss_archetypes: Res<Assets<BossArchetype>>,
+    definitions: Res<Assets<WeaponDefinition>>,
+) {
+    let resume = match resume {
+        Some(resume) => resume,
+        None => return,
+    };
+    let run = &resume.0;
+
+    cmd.remove_resource::<ResumeRun>();
+
+    if let Ok((mut ship, mut hp, mut transform, mut heading, mut velocity, mut weapons)) =
+        ships.get_mut(player_ship.0)
+    {
+        ship.score = run.ship.score;
+        hp.current = run.ship.hitpoints.min(hp.max);
+        transform.translation = run.ship.position;
+        velocity.0 = run.ship.velocity;
+        heading.angle = run.ship.heading;
+        weapons.current = run.ship.weapon.min(weapons.slots.saturating_sub(1));
+    }
+
+    for (weapon_slot, cannon, launcher) in weapons.iter_mut() {
+        let progress = match run.ship.weapons.get(weapon_slot.slot) {
+            Some(progress) if weapon_slot.system == player_ship.0 => progress,
+            _ => continue,
+        };
+
+        if let Some(mut cannon) = cannon {
+            cannon.restore(progress);
+        } else if let Some(mut launcher) = launcher {
+            launcher.restore(progress);
+        }
+    }
+
+    for entity in chunks.iter() {
+        cmd.entity(entity).despawn();
+    }
+
+    let generator = LevelGenerator::restore(
+        run.seed,
+        run.chunks.clone(),
+        run.destroyed.clone(),
+        &mut cmd,
+        &materials,
+    );
+    cmd.insert_resource(generator);
+    let rng = run.rng.clone();
+    cmd.insert_resource(rng.unwrap_or_else(|| SimulationRng::new(run.seed ^ run.tick)));
+    sim_time.tick = run.tick;
+    cmd.insert_resource(run.stats);
+    cmd.insert_resource(run.director.clone());
+
+    for asteroid in run.asteroids.iter() {
+        let handle = asset_server.load(asteroid.archetype.as_str());
+        if let Some(archetype) = archetypes.get(&handle) {
+            let entity = spawn_asteroid(
+                &mut cmd,
+                &handle,
+                archetype,
+                Transform {
+                    translation: asteroid.position,
+                    rotation: asteroid.rotation,
+                    ..default()
+                },
+                asteroid.velocity,
+                asteroid.spin,
+                asteroid.chunk.is_none().then_some(player_ship.0),
+            );
+            cmd.entity(entity).insert(Hitpoints {
+                current: asteroid.hitpoints.min(archetype.hitpoints),
+                max: archetype.hitpoints,
+            });
+            if let Some(chunk) = &asteroid.chunk {
+                cmd.entity(entity).insert(chunk.clone());
+            }
+            if let Some(field) = asteroid.field {
+                cmd.entity(entity).insert(field);
+            }
+        }
+    }
+
+    for shard in run.shards.iter() {
+        let handle = asset_server.load(shard.archetype.as_str());
+        if let Some(archetype) = archetypes.get(&handle) {
+            spawn_shard(
+                &mut cmd,
+                &handle,
+                archetype,
+                Transform {
+                    translation: shard.position,
+                    rotation: shard.rotation,
+                    scale: shard.scale,
+                },
+                shard.velocity,
+                shard.spin,
+                Lifetime::from_seconds(shard.seconds_left),
+            );
+        }
+    }
+
+    let mut sources = HashMap::default();
+    sources.insert(SavedSource::Player, player_ship.0);
+
+    for (index, enemy) in run.enemies.iter().enumerate() {
+        let handle = asset_server.load(enemy.archetype.as_str());
+        if let Some(archetype) = enemy_archetypes.get(&handle) {
+            let entity = spawn_enemy(
+                &mut cmd,
+                &handle,
+                archetype,
+                &definitions,
+                &asset_server,
+                Transform::from_translation(enemy.position),
+                enemy.strafe,
+                player_ship.0,
+                &enemy.weapons,
+            );
+            cmd.entity(entity)
+                .insert(Hitpoints {
+                    current: enemy.hitpoints.min(archetype.hitpoints),
+                    max: archetype.hitpoints,
+                })
+                .insert(Heading {
+                    angle: enemy.heading,
+                    turn_speed: 0.,
+                })
+                .insert(Velocity(enemy.velocity));
+            sources.insert(SavedSource::Enemy(index), entity);
+        }
+    }
+
+    for (index, boss) in run.bosses.iter().enumerate() {
+        let handle = asset_server.load(boss.archetype.as_str());
+        let archetype = match boss_archetypes.get(&handle) {
+            Some(archetype) => archetype,
+            None => continue,
+        };
+
+        let parts: Vec<_> = boss
+            .parts
+            .iter()
+            .filter_map(|part| Some((part, archetype.parts.get(part.index)?)))
+            .collect();
+        let entity = spawn_boss(
+            &mut cmd,
+            &handle,
+            Transform {
+                translation: boss.position,
+                rotation: boss.rotation,
+                ..default()
+            },
+            player_ship.0,
+            parts.len(),
+        );
+
+        let mut spawned = Vec::new();
+        cmd.entity(entity).with_children(|parent| {
+            for (part, _) in parts.iter() {
+                let part_entity = spawn_boss_part(
+                    parent,
+                    archetype,
+                    part.index,
+                    &definitions,
+                    &asset_server,
+                    &part.weapons,
+                );
+                spawned.extend(part_entity);
+            }
+        });
+
+        for ((part, definition), part_entity) in parts.iter().zip(spawned) {
+            cmd.entity(part_entity).insert(Hitpoints {
+                current: part.hitpoints.min(definition.hitpoints),
+                max: definition.hitpoints,
+            });
+            sources.insert(SavedSource::BossPart(index, part.index), part_entity);
+        }
+    }
+
+    for bullet in run.bullets.iter() {
+        let handle = asset_server.load(bullet.weapon.as_str());
+        if let (Some(definition), Some(source)) =
+            (definitions.get(&handle), sources.get(&bullet.source))
+        {
+            spawn_bullet(
+                &mut cmd,
+                definition,
+                Bullet {
+                    definition: handle.clone(),
+                    already_hit: false,
+                    damage: bullet.damage,
+                    kind: bullet.kind,
+                    source: *source,
+                },
+                Transform {
+                    translation: bullet.position,
+                    rotation: bullet.rotation,
+                    ..default()
+                },
+                bullet.velocity,
+                Lifetime::from_seconds(bullet.seconds_left),
+            );
+        }
+    }
+
+    // missiles pick a new target once restored
+    for missile in run.missiles.iter() {
+        let handle = asset_server.load(missile.weapon.as_str());
+        let definition = definitions.get(&handle);
+        let source = sources.get(&missile.source);
+        if let (Some(definition), Some(source)) = (definition, source) {
+            if let Some(mut restored) = Missile::new(&handle, definition, *source) {
+                restored.damage = missile.damage;
+                restored.kind = missile.kind;
+                spawn_missile(
+                    &mut cmd,
+                    definition,
+                    restored,
+                    Transform {
+                        translation: missile.position,
+                        rotation: missile.rotation,
+                        ..default()
+                    },
+                    missile.velocity,
+                    Lifetime::from_seconds(missile.seconds_left),
+                );
+            }
+        }
+    }
+}
+
+fn discard_finished_run(player_ship: Res<PlayerSpaceship>, ships: Query<&Hitpoints>) {
+    if ships.get(player_ship.0).map_or(true, |hp| hp.is_dead()) {
+        SavedRun::delete();
+    }
+}
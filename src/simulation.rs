@@ -0,0 +1,363 @@
+use std::time::Duration;
+
+use bevy::asset::{Asset, AssetPlugin, LoadState};
+use bevy::ecs::schedule::ShouldRun;
+use bevy::hierarchy::HierarchyPlugin;
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::transform::{transform_propagate_system, TransformPlugin};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::bosses::*;
+use crate::collision::*;
+use crate::config::*;
+use crate::controls::*;
+use crate::damage::*;
+use crate::director::*;
+use crate::enemies::*;
+use crate::high_scores::*;
+use crate::replay::*;
+use crate::ron_asset::RonAssetPlugin;
+use crate::spatial::*;
+
+pub const SIMULATION_STAGE: &str = "simulation_stage";
+
+/// Length of a single simulation tick.
+pub const TIMESTEP: f32 = 1.0 / 60.0;
+
+/// Most ticks run in a single frame, the simulation slows down instead of falling further
+/// behind when frames take longer than that.
+pub const MAX_TICKS_PER_FRAME: u32 = 5;
+
+/// Ordering of the systems within a single simulation tick.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimulationLabel {
+    Input,
+    Movement,
+    Collision,
+    Damage,
+    Cleanup,
+}
+
+/// Time as seen by the gameplay systems, always advancing by [`TIMESTEP`].
+pub struct SimulationTime {
+    accumulator: Duration,
+    looping: bool,
+    lockstep: bool,
+    pub paused: bool,
+    pub tick: u64,
+}
+
+impl SimulationTime {
+    /// Runs as many ticks as fit into the real time that has passed.
+    pub fn realtime() -> Self {
+        SimulationTime {
+            accumulator: Duration::ZERO,
+            looping: false,
+            lockstep: false,
+            paused: false,
+            tick: 0,
+        }
+    }
+
+    /// Runs exactly one tick every frame, regardless of the real time.
+    pub fn lockstep() -> Self {
+        SimulationTime {
+            lockstep: true,
+            ..SimulationTime::realtime()
+        }
+    }
+
+    pub fn delta(&self) -> Duration {
+        Duration::from_secs_f32(TIMESTEP)
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        TIMESTEP
+    }
+}
+
+impl Default for SimulationTime {
+    fn default() -> Self {
+        SimulationTime::realtime()
+    }
+}
+
+/// The only source of randomness gameplay systems are allowed to use.
+///
+/// Serializable, so a saved run continues with the exact same numbers.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SimulationRng(pub ChaCha8Rng);
+
+impl SimulationRng {
+    pub fn new(seed: u64) -> Self {
+        SimulationRng(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+fn simulation_step(
+    time: Res<Time>,
+    state: Res<State<AppState>>,
+    mut sim_time: ResMut<SimulationTime>,
+) -> ShouldRun {
+    if state.current() != &AppState::InGame || sim_time.paused {
+        sim_time.accumulator = Duration::ZERO;
+        sim_time.looping = false;
+        return ShouldRun::No;
+    }
+
+    if sim_time.lockstep {
+        // the stage gets asked again after every tick, only say yes to the first ask
+        sim_time.looping = !sim_time.looping;
+        if sim_time.looping {
+            sim_time.tick += 1;
+            return ShouldRun::YesAndCheckAgain;
+        }
+        return ShouldRun::No;
+    }
+
+    let step = sim_time.delta();
+    if !sim_time.looping {
+        sim_time.accumulator =
+            (sim_time.accumulator + time.delta()).min(step * MAX_TICKS_PER_FRAME);
+    }
+
+    if sim_time.accumulator >= step {
+        sim_time.accumulator -= step;
+        sim_time.looping = true;
+        sim_time.tick += 1;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        sim_time.looping = false;
+        ShouldRun::No
+    }
+}
+
+fn toggle_pause(mut actions: ResMut<ActionState>, mut sim_time: ResMut<SimulationTime>) {
+    if actions.take_presses(Action::Pause) % 2 == 1 {
+        sim_time.paused = !sim_time.paused;
+    }
+}
+
+/// Assets that have to be loaded before leaving [`AppState::Loading`].
+#[derive(Default)]
+pub struct LoadingAssets(Vec<HandleUntyped>);
+
+impl LoadingAssets {
+    pub fn track<T: Asset>(&mut self, handle: &Handle<T>) {
+        self.0.push(handle.clone_untyped());
+    }
+
+    /// Loads and tracks every asset in the folder at `path`, sorted by path so they are
+    /// always in the same order. A missing folder is logged and gives no assets.
+    pub fn load_folder<T: Asset>(world: &mut World, path: &str) -> Vec<Handle<T>> {
+        let asset_server = world.resource::<AssetServer>();
+        let mut handles: Vec<Handle<T>> = match asset_server.load_folder(path) {
+            Ok(handles) => handles.into_iter().map(|handle| handle.typed()).collect(),
+            Err(e) => {
+                error!("not able to load the {} folder: {}", path, e);
+                return Vec::new();
+            }
+        };
+
+        handles.sort_by_key(|handle| {
+            asset_server
+                .get_handle_path(handle)
+                .map(|path| path.path().to_owned())
+        });
+
+        let mut loading = world.resource_mut::<LoadingAssets>();
+        for handle in handles.iter() {
+            loading.track(handle);
+        }
+
+        handles
+    }
+}
+
+/// Leaves [`AppState::Loading`] once the tracked assets are loaded, the ones that failed to
+/// load are logged and left out.
+fn finish_loading(
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+    after_loading: Res<AfterLoading>,
+    mut states: ResMut<State<AppState>>,
+) {
+    match asset_server.get_group_load_state(loading.0.iter().map(|h| h.id)) {
+        LoadState::Loaded => states.replace(after_loading.0.clone()).unwrap(),
+        LoadState::Failed => loading.0.retain(|handle| {
+            if asset_server.get_load_state(handle) != LoadState::Failed {
+                return true;
+            }
+
+            match asset_server.get_handle_path(handle) {
+                Some(path) => error!("not able to load {}", path.path().display()),
+                None => error!("not able to load asset {:?}", handle.id),
+            }
+            false
+        }),
+        _ => {}
+    }
+}
+
+struct AfterLoading(AppState);
+
+/// Registers everything needed to play the game without any rendering.
+///
+/// Starts in [`AppState::Loading`] and switches to `after_loading` once all
+/// definitions are loaded, the game continues to [`AppState::Menu`] while headless
+/// runs go straight into [`AppState::InGame`].
+///
+/// Gameplay runs in its own single threaded stage at a fixed rate, so a run plays out
+/// the same no matter the frame rate.
+pub struct GameSimulationPlugin {
+    pub after_loading: AppState,
+}
+
+impl Plugin for GameSimulationPlugin {
+    fn build(&self, app: &mut App) {
+        use SimulationLabel::*;
+
+        app.add_state(AppState::Loading)
+            .insert_resource(AfterLoading(self.after_loading.clone()))
+            .init_resource::<LoadingAssets>()
+            .add_plugin(RonAssetPlugin::<WeaponDefinition>::default())
+            .add_plugin(RonAssetPlugin::<AsteroidArchetype>::default())
+            .add_plugin(RonAssetPlugin::<EnemyArchetype>::default())
+            .add_plugin(RonAssetPlugin::<BossArchetype>::default())
+            .init_resource::<WeaponDefinitions>()
+            .init_resource::<AsteroidArchetypes>()
+            .init_resource::<EnemyArchetypes>()
+            .init_resource::<BossArchetypes>()
+            .add_plugin(RonAssetPlugin::<GameConfig>::default())
+            .init_resource::<GameConfig>()
+            .init_resource::<GameConfigHandle>()
+            .add_system_to_stage(CoreStage::PreUpdate, apply_game_config)
+            .add_system_set(SystemSet::on_update(AppState::Loading).with_system(finish_loading))
+            .add_system_set(SystemSet::on_exit(AppState::Loading).with_system(init_game_config))
+            .insert_resource(MouseWorldPos::default())
+            .insert_resource(LevelGenerator::new(123))
+            .init_resource::<SimulationTime>()
+            .init_resource::<PlayerInput>()
+            .init_resource::<RunStats>()
+            .init_resource::<Director>()
+            .init_resource::<SpatialIndex>()
+            .init_resource::<Contacts>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .add_event::<DamageEvent>()
+            .add_event::<HealEvent>()
+            .add_event::<DeathEvent>()
+            .init_resource::<ActionState>()
+            .init_resource::<GameMaterials>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::InGame)
+                    .with_system(start_game)
+                    .with_system(start_replay.before(start_game)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::InGame)
+                    .with_system(cleanup::<CleanupAfterGame>)
+                    .with_system(finish_replay),
+            )
+            .add_system_set(SystemSet::on_update(AppState::InGame).with_system(toggle_pause))
+            .add_system_to_stage(CoreStage::Last, finish_replay_on_exit)
+            .add_stage_after(
+                CoreStage::Update,
+                SIMULATION_STAGE,
+                SystemStage::single_threaded().with_run_criteria(simulation_step),
+            )
+            // the systems of every set run one after another, bevy picks a new order for unordered
+            // systems every time the app starts and a run would play out differently
+            .add_system_set_to_stage(
+                SIMULATION_STAGE,
+                SystemSet::new()
+                    .label(Input)
+                    .with_system(sample_player_input)
+                    .with_system(play_replay.after(sample_player_input))
+                    .with_system(record_replay.after(play_replay)),
+            )
+            .add_system_set_to_stage(
+                SIMULATION_STAGE,
+                SystemSet::new()
+                    .label(Movement)
+                    .after(Input)
+                    .with_system(ship_movement)
+                    .with_system(weapon_system_switch_weapon.after(ship_movement))
+                    .with_system(weapon_system_fire.after(weapon_system_switch_weapon))
+                    .with_system(enemy_ai.after(weapon_system_fire))
+                    .with_system(boss_phases.after(enemy_ai))
+                    .with_system(ship_cannon.after(boss_phases))
+                    .with_system(ship_laser.after(ship_cannon))
+                    .with_system(ship_missile_launcher.after(ship_laser))
+                    .with_system(missile_guidance.after(ship_missile_launcher))
+                    .with_system(laser_beam_init.after(missile_guidance))
+                    .with_system(magnets.after(laser_beam_init))
+                    .with_system(movement.after(magnets))
+                    .with_system(continuous_rotation.after(movement)),
+            )
+            .add_system_set_to_stage(
+                SIMULATION_STAGE,
+                SystemSet::new()
+                    .label(Collision)
+                    .after(Movement)
+                    .with_system(transform_propagate_system)
+                    .with_system(update_spatial_index.after(transform_propagate_system))
+                    .with_system(detect_collisions.after(update_spatial_index))
+                    .with_system(laser_beam.after(detect_collisions)),
+            )
+            .add_system_set_to_stage(
+                SIMULATION_STAGE,
+                SystemSet::new()
+                    .label(Damage)
+                    .after(Collision)
+                    .with_system(bullets_hit)
+                    .with_system(laser_beams_hit.after(bullets_hit))
+                    .with_system(missiles_hit.after(laser_beams_hit))
+                    .with_system(bounce.after(missiles_hit))
+                    .with_system(ship_eats_shards.after(bounce))
+                    .with_system(apply_damage.after(ship_eats_shards))
+                    .with_system(asteroid_damage.after(apply_damage))
+                    .with_system(asteroid_destroyed.after(asteroid_damage))
+                    .with_system(enemy_destroyed.after(asteroid_destroyed))
+                    .with_system(boss_part_destroyed.after(enemy_destroyed))
+                    .with_system(game_over.after(boss_part_destroyed)),
+            )
+            .add_system_set_to_stage(
+                SIMULATION_STAGE,
+                SystemSet::new()
+                    .label(Cleanup)
+                    .after(Damage)
+                    .with_system(count_run_ticks)
+                    .with_system(lifetime.after(count_run_ticks))
+                    .with_system(maximum_distance_from.after(lifetime))
+                    .with_system(direct_waves.after(maximum_distance_from))
+                    .with_system(spawn_wave_enemies.after(direct_waves))
+                    .with_system(spawn_wave_boss.after(spawn_wave_enemies))
+                    .with_system(generate_background.after(spawn_wave_boss))
+                    .with_system(cleanup_chunks.after(generate_background)),
+            );
+    }
+}
+
+/// Builds an app running the game without a window or GPU.
+///
+/// Nothing drives the app on its own, every [`App::update`] steps the simulation by one tick
+/// (once the definitions are loaded).
+pub fn headless_app() -> App {
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(InputPlugin)
+        .insert_resource(SimulationTime::lockstep())
+        .add_plugin(GameSimulationPlugin {
+            after_loading: AppState::InGame,
+        });
+
+    app
+}
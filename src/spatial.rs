@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::collider::{Collider, Shape};
+
+/// Size of a single cell of the [`SpatialIndex`].
+pub const CELL_SIZE: f32 = 128.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Cell(i32, i32);
+
+impl Cell {
+    fn containing(pos: Vec2) -> Cell {
+        Cell(
+            (pos.x / CELL_SIZE).floor() as i32,
+            (pos.y / CELL_SIZE).floor() as i32,
+        )
+    }
+
+    fn covering(min: Vec2, max: Vec2) -> impl Iterator<Item = Cell> {
+        let Cell(min_x, min_y) = Cell::containing(min);
+        let Cell(max_x, max_y) = Cell::containing(max);
+
+        (min_x..=max_x).flat_map(move |x| (min_y..=max_y).map(move |y| Cell(x, y)))
+    }
+}
+
+/// Axis aligned bounds of an indexed entity.
+#[derive(Clone, Copy, Debug)]
+struct Bounds {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl Bounds {
+    fn overlaps(&self, other: &Bounds) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+}
+
+/// Uniform grid of everything with a [`Collider`], rebuilt every tick.
+///
+/// Queries only return candidates whose bounds overlap the queried area, the exact test
+/// is up to the caller. Candidates come sorted, so the order doesn't depend on the grid.
+#[derive(Default)]
+pub struct SpatialIndex {
+    cells: HashMap<Cell, Vec<Entity>>,
+    bounds: HashMap<Entity, Bounds>,
+}
+
+impl SpatialIndex {
+    fn clear(&mut self) {
+        // keep the allocations of the cells that stay occupied
+        self.cells.retain(|_, entities| {
+            let occupied = !entities.is_empty();
+            entities.clear();
+            occupied
+        });
+        self.bounds.clear();
+    }
+
+    fn insert(&mut self, entity: Entity, bounds: Bounds) {
+        for cell in Cell::covering(bounds.min, bounds.max) {
+            self.cells.entry(cell).or_default().push(entity);
+        }
+        self.bounds.insert(entity, bounds);
+    }
+
+    fn query(&self, area: Bounds) -> Vec<Entity> {
+        let mut found: Vec<Entity> = Cell::covering(area.min, area.max)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(|entity| self.bounds[entity].overlaps(&area))
+            .collect();
+
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    /// Entities with bounds overlapping the bounds of `shape`.
+    pub fn query_shape(&self, shape: &Shape) -> Vec<Entity> {
+        let (min, max) = shape.bounds();
+        self.query(Bounds { min, max })
+    }
+
+    /// Entities with bounds overlapping the box around the circle.
+    pub fn query_circle(&self, center: Vec2, radius: f32) -> Vec<Entity> {
+        self.query(Bounds {
+            min: center - radius,
+            max: center + radius,
+        })
+    }
+
+    /// Entities with bounds within `margin` of the segment from `start` to `end`.
+    pub fn query_segment(&self, start: Vec2, end: Vec2, margin: f32) -> Vec<Entity> {
+        let length = start.distance(end);
+        if length <= CELL_SIZE {
+            let area = Bounds {
+                min: start.min(end) - Vec2::splat(margin),
+                max: start.max(end) + Vec2::splat(margin),
+            };
+            return self.query(area);
+        }
+
+        // long segments are split up, so a diagonal doesn't query the whole box around it
+        let steps = (length / CELL_SIZE).ceil() as usize;
+        let mut found: Vec<Entity> = (0..steps)
+            .flat_map(|step| {
+                let from = start.lerp(end, step as f32 / steps as f32);
+                let to = start.lerp(end, (step + 1) as f32 / steps as f32);
+                self.query_segment(from, to, margin)
+            })
+            .collect();
+
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+pub fn update_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    indexed: Query<(Entity, &GlobalTransform, &Collider)>,
+) {
+    index.clear();
+
+    for (entity, transform, collider) in indexed.iter() {
+        let (min, max) = collider.shape(transform).bounds();
+        index.insert(entity, Bounds { min, max });
+    }
+}
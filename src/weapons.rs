@@ -1,19 +1,178 @@
+use std::path::Path;
+use std::time::Duration;
+
 use super::*;
+use crate::bosses::BossPart;
+use crate::collider::Shape;
+use crate::collision::CollisionStarted;
+use crate::controls::{Aim, PlayerInput};
+use crate::damage::{DamageEvent, DamageKind};
+use crate::enemies::Enemy;
+use crate::fire_pattern::{FirePattern, Trigger, TriggerInput};
+use crate::ron_asset::{texture_handle, RonAsset};
+use crate::spatial::SpatialIndex;
+use bevy::asset::{AssetPath, LoadContext};
+use bevy::reflect::TypeUuid;
 use bevy::{ecs::component::Component, prelude::*, sprite::Anchor, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+/// Description of a weapon, loaded from a `.weapon.ron` file.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "1f0bd4a5-51c4-4b8e-9a44-2f6d2d2b5c0e"]
+pub struct WeaponDefinition {
+    /// Texture of the projectile or the beam.
+    pub sprite: String,
+    #[serde(skip)]
+    pub texture: Handle<Image>,
+    pub kind: WeaponKind,
+}
+
+#[derive(Deserialize)]
+pub enum WeaponKind {
+    Projectile {
+        /// Seconds between two shots.
+        cooldown: f32,
+        speed: f32,
+        damage: u32,
+        damage_kind: DamageKind,
+        /// Seconds until the projectile disappears.
+        lifetime: f32,
+        collider: Collider,
+        #[serde(default)]
+        pattern: FirePattern,
+    },
+    Beam {
+        range: f32,
+        damage: u32,
+        damage_kind: DamageKind,
+        /// Seconds the beam has to stay on a target to deal its damage.
+        damage_tick: f32,
+    },
+    Missile {
+        /// Seconds between two launches.
+        cooldown: f32,
+        speed: f32,
+        /// Radians per second the missile turns towards its target.
+        turn_rate: f32,
+        /// Half of the angle (in radians) in front of the missile it acquires targets in.
+        seek_angle: f32,
+        seek_range: f32,
+        /// Damage dealt to everything within `blast_radius` on impact.
+        damage: u32,
+        damage_kind: DamageKind,
+        blast_radius: f32,
+        /// Seconds of fuel, the missile disappears once burnt out.
+        fuel: f32,
+        collider: Collider,
+        #[serde(default)]
+        pattern: FirePattern,
+    },
+}
+
+impl WeaponDefinition {
+    /// Hitbox of the projectiles or missiles, beams have none.
+    fn collider(&self) -> Option<&Collider> {
+        match &self.kind {
+            WeaponKind::Projectile { collider, .. } | WeaponKind::Missile { collider, .. } => {
+                Some(collider)
+            }
+            WeaponKind::Beam { .. } => None,
+        }
+    }
+}
+
+impl RonAsset for WeaponDefinition {
+    const EXTENSIONS: &'static [&'static str] = &["weapon.ron"];
+
+    fn load_textures(&mut self, load_context: &mut LoadContext) -> Vec<AssetPath<'static>> {
+        let mut textures = Vec::new();
+        self.texture = texture_handle(load_context, &self.sprite, &mut textures);
+        textures
+    }
+}
+
+/// All the weapon definitions in the `weapons` folder, loaded before the game starts.
+pub struct WeaponDefinitions(Vec<Handle<WeaponDefinition>>);
+
+impl FromWorld for WeaponDefinitions {
+    fn from_world(world: &mut World) -> Self {
+        WeaponDefinitions(LoadingAssets::load_folder(world, "weapons"))
+    }
+}
+
+impl WeaponDefinitions {
+    /// Handles of the weapons at `paths` in the same order, paths outside of the `weapons`
+    /// folder are left out.
+    pub fn loadout(
+        &self,
+        asset_server: &AssetServer,
+        paths: &[String],
+    ) -> Vec<Handle<WeaponDefinition>> {
+        paths
+            .iter()
+            .filter_map(|path| {
+                let handle = self.0.iter().find(|handle| {
+                    asset_server
+                        .get_handle_path(*handle)
+                        .is_some_and(|asset| asset.path() == Path::new(path))
+                });
+                if handle.is_none() {
+                    warn!("weapon {} is not in the weapons folder", path);
+                }
+                handle.cloned()
+            })
+            .collect()
+    }
+}
 
 #[derive(Component)]
 pub struct WeaponSystem {
     pub current: usize,
     pub slots: usize,
     pub is_firing: bool,
+    /// Fires charged shots once fully charged, for AI controllers that just hold the trigger.
+    pub auto_release: bool,
 }
 
+/// Where the weapons of a [`WeaponSystem`] shoot, written by whoever controls the ship.
+#[derive(Component, Default, Clone, Copy)]
+pub struct AimTarget(pub Aim);
+
 impl WeaponSystem {
+    pub fn new(slots: usize) -> Self {
+        WeaponSystem {
+            current: 0,
+            slots,
+            is_firing: false,
+            auto_release: false,
+        }
+    }
+
+    /// System held by an AI controller, see [`WeaponSystem::auto_release`].
+    pub fn automatic(slots: usize) -> Self {
+        WeaponSystem {
+            auto_release: true,
+            ..WeaponSystem::new(slots)
+        }
+    }
+
     fn is_firing(&self, weapon: &WeaponSlot) -> bool {
         self.is_firing && weapon.slot == self.current
     }
 
+    fn trigger(&self, weapon: &WeaponSlot) -> TriggerInput {
+        TriggerInput {
+            pulled: self.is_firing,
+            selected: weapon.slot == self.current,
+            auto_release: self.auto_release,
+        }
+    }
+
     fn prev(&mut self) -> usize {
+        if self.slots == 0 {
+            return self.current;
+        }
+
         if self.current == 0 {
             self.current = self.slots - 1;
         } else {
@@ -23,7 +182,11 @@ impl WeaponSystem {
     }
 
     fn next(&mut self) -> usize {
-        if self.current == self.slots - 1 {
+        if self.slots == 0 {
+            return self.current;
+        }
+
+        if self.current >= self.slots - 1 {
             self.current = 0;
         } else {
             self.current += 1;
@@ -38,48 +201,145 @@ pub struct WeaponSlot {
     pub slot: usize,
 }
 
-#[derive(Bundle)]
-pub struct WeaponBundle<W: Component> {
-    weapon_slot: WeaponSlot,
-    transform: Transform,
-    global_transform: GlobalTransform,
-    weapon: W,
+pub use bundle::WeaponBundle;
+
+// the `Bundle` derive of bevy 0.7 forgets every field after moving it out, the lint can't be
+// allowed on the derived impl alone
+#[allow(clippy::forget_non_drop)]
+mod bundle {
+    use bevy::prelude::*;
+
+    use super::WeaponSlot;
+
+    #[derive(Bundle)]
+    pub struct WeaponBundle<W: Component> {
+        weapon_slot: WeaponSlot,
+        transform: Transform,
+        global_transform: GlobalTransform,
+        weapon: W,
+    }
+
+    impl<W: Component> WeaponBundle<W> {
+        pub fn new(weapon: W, slot: usize, system: Entity) -> Self {
+            Self {
+                weapon_slot: WeaponSlot { slot, system },
+                transform: Transform::default(),
+                global_transform: GlobalTransform::default(),
+                weapon,
+            }
+        }
+    }
+}
+
+/// Spawns the weapon described by `definition` into `slot` of the weapon system `system`,
+/// picking up the `progress` of a saved run.
+pub fn spawn_weapon(
+    parent: &mut ChildBuilder,
+    definitions: &Assets<WeaponDefinition>,
+    definition: &Handle<WeaponDefinition>,
+    slot: usize,
+    system: Entity,
+    progress: Option<&WeaponProgress>,
+) {
+    let kind = match definitions.get(definition) {
+        Some(weapon) => &weapon.kind,
+        None => {
+            warn!("weapon for slot {} is not loaded", slot);
+            return;
+        }
+    };
+
+    match kind {
+        WeaponKind::Projectile { cooldown, .. } => {
+            let mut cannon = WeaponCannon {
+                definition: definition.clone(),
+                cooldown: Timer::from_seconds(*cooldown, false),
+                trigger: Trigger::default(),
+            };
+            if let Some(progress) = progress {
+                progress.apply(&mut cannon.cooldown, &mut cannon.trigger);
+            }
+            parent.spawn_bundle(WeaponBundle::new(cannon, slot, system));
+        }
+        WeaponKind::Beam { .. } => {
+            let laser = WeaponLaser {
+                definition: definition.clone(),
+                firing: None,
+            };
+            parent.spawn_bundle(WeaponBundle::new(laser, slot, system));
+        }
+        WeaponKind::Missile { cooldown, .. } => {
+            let mut launcher = WeaponMissileLauncher {
+                definition: definition.clone(),
+                cooldown: Timer::from_seconds(*cooldown, false),
+                trigger: Trigger::default(),
+            };
+            if let Some(progress) = progress {
+                progress.apply(&mut launcher.cooldown, &mut launcher.trigger);
+            }
+            parent.spawn_bundle(WeaponBundle::new(launcher, slot, system));
+        }
+    }
 }
 
-impl<W: Component> WeaponBundle<W> {
-    pub fn new(weapon: W, slot: usize, system: Entity) -> Self {
-        Self {
-            weapon_slot: WeaponSlot { slot, system },
-            transform: Transform::default(),
-            global_transform: GlobalTransform::default(),
-            weapon,
+/// Cooldown and trigger of a cannon or missile launcher, kept with a saved run.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct WeaponProgress {
+    /// Seconds of the cooldown already elapsed.
+    cooldown: f32,
+    trigger: Trigger,
+}
+
+impl WeaponProgress {
+    fn of(cooldown: &Timer, trigger: &Trigger) -> Self {
+        WeaponProgress {
+            cooldown: cooldown.elapsed_secs(),
+            trigger: trigger.clone(),
         }
     }
+
+    fn apply(&self, cooldown: &mut Timer, trigger: &mut Trigger) {
+        let elapsed = Duration::from_secs_f32(self.cooldown.max(0.));
+        cooldown.set_elapsed(elapsed.min(cooldown.duration()));
+        *trigger = self.trigger.clone();
+    }
 }
 
+/// Weapon shooting projectiles, see [`WeaponKind::Projectile`].
 #[derive(Component)]
-pub struct WeaponCannon(Timer);
+pub struct WeaponCannon {
+    definition: Handle<WeaponDefinition>,
+    cooldown: Timer,
+    trigger: Trigger,
+}
 
-impl Default for WeaponCannon {
-    fn default() -> Self {
-        WeaponCannon(Timer::from_seconds(0.150, false))
+impl WeaponCannon {
+    pub fn progress(&self) -> WeaponProgress {
+        WeaponProgress::of(&self.cooldown, &self.trigger)
+    }
+
+    pub fn restore(&mut self, progress: &WeaponProgress) {
+        progress.apply(&mut self.cooldown, &mut self.trigger);
     }
 }
-#[derive(Component, Default)]
+
+#[derive(Component)]
 pub struct Bullet {
+    /// Weapon that fired the bullet.
+    pub definition: Handle<WeaponDefinition>,
     pub already_hit: bool,
+    pub damage: u32,
+    pub kind: DamageKind,
+    /// Ship that fired the bullet.
+    pub source: Entity,
 }
 
+/// Weapon firing a continuous beam, see [`WeaponKind::Beam`].
 #[derive(Component)]
-pub enum WeaponLaser {
-    Idle,
-    Firing(Vec3),
-}
-
-impl Default for WeaponLaser {
-    fn default() -> Self {
-        WeaponLaser::Idle
-    }
+pub struct WeaponLaser {
+    definition: Handle<WeaponDefinition>,
+    /// Direction of the beam while firing.
+    firing: Option<Vec3>,
 }
 
 #[derive(Component)]
@@ -91,101 +351,465 @@ pub struct LaserBeam {
 #[derive(Component)]
 pub struct LaserImpact;
 
+/// Weapon launching homing missiles, see [`WeaponKind::Missile`].
+#[derive(Component)]
+pub struct WeaponMissileLauncher {
+    definition: Handle<WeaponDefinition>,
+    cooldown: Timer,
+    trigger: Trigger,
+}
+
+impl WeaponMissileLauncher {
+    pub fn progress(&self) -> WeaponProgress {
+        WeaponProgress::of(&self.cooldown, &self.trigger)
+    }
+
+    pub fn restore(&mut self, progress: &WeaponProgress) {
+        progress.apply(&mut self.cooldown, &mut self.trigger);
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct Missile {
+    /// Launcher the missile came from.
+    pub definition: Handle<WeaponDefinition>,
+    speed: f32,
+    turn_rate: f32,
+    seek_angle: f32,
+    seek_range: f32,
+    pub damage: u32,
+    pub kind: DamageKind,
+    blast_radius: f32,
+    /// Ship that launched the missile.
+    pub source: Entity,
+    target: Option<Entity>,
+}
+
+impl Missile {
+    /// Missile of the launcher `definition` not locked onto anything yet, `None` when the
+    /// definition isn't a missile launcher.
+    pub fn new(
+        handle: &Handle<WeaponDefinition>,
+        definition: &WeaponDefinition,
+        source: Entity,
+    ) -> Option<Self> {
+        match definition.kind {
+            WeaponKind::Missile {
+                speed,
+                turn_rate,
+                seek_angle,
+                seek_range,
+                damage,
+                damage_kind,
+                blast_radius,
+                ..
+            } => Some(Missile {
+                definition: handle.clone(),
+                speed,
+                turn_rate,
+                seek_angle,
+                seek_range,
+                damage,
+                kind: damage_kind,
+                blast_radius,
+                source,
+                target: None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Spawns a bullet fired by the weapon `definition`.
+pub fn spawn_bullet(
+    commands: &mut Commands,
+    definition: &WeaponDefinition,
+    bullet: Bullet,
+    transform: Transform,
+    velocity: Vec3,
+    lifetime: Lifetime,
+) {
+    let collider = match definition.collider() {
+        Some(collider) => collider.clone(),
+        None => return,
+    };
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: definition.texture.clone(),
+            transform,
+            ..default()
+        })
+        .insert(CleanupAfterGame)
+        .insert(bullet)
+        .insert(Velocity::from(velocity))
+        .insert(lifetime)
+        .insert(collider);
+}
+
+/// Spawns a missile launched by the weapon `definition`.
+pub fn spawn_missile(
+    commands: &mut Commands,
+    definition: &WeaponDefinition,
+    missile: Missile,
+    transform: Transform,
+    velocity: Vec3,
+    lifetime: Lifetime,
+) {
+    let collider = match definition.collider() {
+        Some(collider) => collider.clone(),
+        None => return,
+    };
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: definition.texture.clone(),
+            transform,
+            ..default()
+        })
+        .insert(CleanupAfterGame)
+        .insert(Velocity::from(velocity))
+        .insert(missile)
+        .insert(lifetime)
+        .insert(collider);
+}
+
 pub fn weapon_system_switch_weapon(
-    mut scroll_reader: EventReader<MouseWheel>,
+    input: Res<PlayerInput>,
+    player_ship: Res<PlayerSpaceship>,
     mut weapon_systems: Query<&mut WeaponSystem>,
 ) {
-    for event in scroll_reader.iter() {
-        for mut system in weapon_systems.iter_mut() {
-            match event.y.total_cmp(&0.) {
-                std::cmp::Ordering::Less => system.prev(),
-                std::cmp::Ordering::Greater => system.next(),
-                _ => continue,
-            };
+    if let Ok(mut system) = weapon_systems.get_mut(player_ship.0) {
+        for _ in 0..input.switch_weapon.unsigned_abs() {
+            if input.switch_weapon < 0 {
+                system.prev();
+            } else {
+                system.next();
+            }
         }
     }
 }
 
+/// Fires the player's weapons at the player's aim.
 pub fn weapon_system_fire(
-    mut weapon_systems: Query<&mut WeaponSystem>,
-    mouse_input: Res<Input<MouseButton>>,
+    mut weapon_systems: Query<(&mut WeaponSystem, &mut AimTarget)>,
+    player_ship: Res<PlayerSpaceship>,
+    input: Res<PlayerInput>,
 ) {
-    let is_firing = mouse_input.pressed(MouseButton::Left);
-
-    for mut system in weapon_systems.iter_mut() {
-        system.is_firing = is_firing;
+    if let Ok((mut system, mut target)) = weapon_systems.get_mut(player_ship.0) {
+        system.is_firing = input.firing;
+        target.0 = input.aim;
     }
 }
 
-const BULLET_SPEED: f32 = 1000.;
-
 pub fn ship_cannon(
     mut commands: Commands,
     mut query: Query<(&mut WeaponCannon, &WeaponSlot, &GlobalTransform)>,
-    weapon_systems: Query<&WeaponSystem>,
-    time: Res<Time>,
-    materials: Res<GameMaterials>,
-    mouse_pos: Res<MouseWorldPos>,
+    weapon_systems: Query<(&WeaponSystem, &AimTarget)>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    time: Res<SimulationTime>,
+    mut rng: ResMut<SimulationRng>,
 ) {
     for (mut cannon, weapon_slot, transform) in query.iter_mut() {
-        cannon.0.tick(time.delta());
+        let definition = match definitions.get(&cannon.definition) {
+            Some(definition) => definition,
+            None => continue,
+        };
+        let (cooldown, speed, damage, damage_kind, lifetime, pattern) = match &definition.kind {
+            WeaponKind::Projectile {
+                cooldown,
+                speed,
+                damage,
+                damage_kind,
+                lifetime,
+                pattern,
+                ..
+            } => (cooldown, speed, damage, damage_kind, lifetime, pattern),
+            _ => continue,
+        };
 
-        let is_firing = weapon_systems
-            .get(weapon_slot.system)
-            .map_or(false, |system| system.is_firing(weapon_slot));
+        // keep up with the definition being hot reloaded
+        cannon
+            .cooldown
+            .set_duration(Duration::from_secs_f32(*cooldown));
+        cannon.cooldown.tick(time.delta());
 
-        if is_firing && cannon.0.finished() {
-            let shot_direction = mouse_pos.dir_from(transform.translation);
+        let (system, target) = match weapon_systems.get(weapon_slot.system) {
+            Ok(system) => system,
+            Err(_) => continue,
+        };
 
+        // aiming right at the weapon gives no direction to fire in
+        let aim = target.0.dir_from(transform.translation);
+        if aim == Vec3::ZERO {
+            continue;
+        }
+
+        let cannon = &mut *cannon;
+        let input = system.trigger(weapon_slot);
+        let dt = time.delta_seconds();
+        let shot = cannon
+            .trigger
+            .update(pattern, input, &mut cannon.cooldown, dt);
+        let multiplier = match shot {
+            Some(multiplier) => multiplier,
+            None => continue,
+        };
+        let damage = (*damage as f32 * multiplier).round() as u32;
+
+        for shot_direction in pattern.directions(aim, &mut rng.0) {
             let angle = shot_direction.angle_between(Vec3::Y) * -shot_direction.x.signum();
 
-            cannon.0.reset();
-            commands
-                .spawn_bundle(SpriteBundle {
-                    texture: materials.bullet.clone(),
-                    transform: Transform {
-                        translation: transform.translation,
-                        rotation: Quat::from_rotation_z(angle),
-                        ..default()
-                    },
+            spawn_bullet(
+                &mut commands,
+                definition,
+                Bullet {
+                    definition: cannon.definition.clone(),
+                    already_hit: false,
+                    damage,
+                    kind: *damage_kind,
+                    source: weapon_slot.system,
+                },
+                Transform {
+                    translation: transform.translation,
+                    rotation: Quat::from_rotation_z(angle),
                     ..default()
-                })
-                .insert(CleanupAfterGame)
-                .insert(Bullet::default())
-                .insert(Velocity::from(shot_direction.normalize() * BULLET_SPEED))
-                .insert(Lifetime::seconds(3))
-                .insert(Collider(Vec2::new(16., 16.)));
+                },
+                shot_direction.normalize() * *speed,
+                Lifetime::from_seconds(*lifetime),
+            );
         }
     }
 }
 
 pub fn ship_laser(
     mut lasers: Query<(&mut WeaponLaser, &WeaponSlot, &GlobalTransform)>,
-    weapon_systems: Query<&WeaponSystem>,
-    mouse_pos: Res<MouseWorldPos>,
+    weapon_systems: Query<(&WeaponSystem, &AimTarget)>,
 ) {
     for (mut laser, weapon_slot, transform) in lasers.iter_mut() {
-        let is_firing = weapon_systems
+        laser.firing = weapon_systems
             .get(weapon_slot.system)
-            .map_or(false, |system| system.is_firing(weapon_slot));
+            .ok()
+            .filter(|(system, _)| system.is_firing(weapon_slot))
+            .map(|(_, target)| target.0.dir_from(transform.translation))
+            .filter(|direction| *direction != Vec3::ZERO);
+    }
+}
 
-        if is_firing {
-            *laser = WeaponLaser::Firing(mouse_pos.dir_from(transform.translation));
-        } else {
-            *laser = WeaponLaser::Idle;
+pub fn ship_missile_launcher(
+    mut commands: Commands,
+    mut launchers: Query<(&mut WeaponMissileLauncher, &WeaponSlot, &GlobalTransform)>,
+    weapon_systems: Query<(&WeaponSystem, &AimTarget)>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    time: Res<SimulationTime>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    for (mut launcher, weapon_slot, transform) in launchers.iter_mut() {
+        let definition = match definitions.get(&launcher.definition) {
+            Some(definition) => definition,
+            None => continue,
+        };
+        let (cooldown, fuel, pattern) = match &definition.kind {
+            WeaponKind::Missile {
+                cooldown,
+                fuel,
+                pattern,
+                ..
+            } => (*cooldown, *fuel, pattern),
+            _ => continue,
+        };
+        let missile = match Missile::new(&launcher.definition, definition, weapon_slot.system) {
+            Some(missile) => missile,
+            None => continue,
+        };
+
+        // keep up with the definition being hot reloaded
+        launcher
+            .cooldown
+            .set_duration(Duration::from_secs_f32(cooldown));
+        launcher.cooldown.tick(time.delta());
+
+        let (system, target) = match weapon_systems.get(weapon_slot.system) {
+            Ok(system) => system,
+            Err(_) => continue,
+        };
+
+        // aiming right at the weapon gives no direction to fire in
+        let aim = target.0.dir_from(transform.translation);
+        if aim == Vec3::ZERO {
+            continue;
         }
+
+        let launcher = &mut *launcher;
+        let input = system.trigger(weapon_slot);
+        let dt = time.delta_seconds();
+        let shot = launcher
+            .trigger
+            .update(pattern, input, &mut launcher.cooldown, dt);
+        let multiplier = match shot {
+            Some(multiplier) => multiplier,
+            None => continue,
+        };
+
+        for direction in pattern.directions(aim, &mut rng.0) {
+            let missile = Missile {
+                damage: (missile.damage as f32 * multiplier).round() as u32,
+                ..missile.clone()
+            };
+
+            let velocity = direction.normalize_or_zero() * missile.speed;
+            spawn_missile(
+                &mut commands,
+                definition,
+                missile,
+                Transform {
+                    translation: transform.translation,
+                    rotation: Quat::from_rotation_z(Heading::angle_of(direction)),
+                    ..default()
+                },
+                velocity,
+                Lifetime::from_seconds(fuel),
+            );
+        }
+    }
+}
+
+/// Locks missiles onto the nearest target in front of them and turns them towards it.
+#[allow(clippy::type_complexity)]
+pub fn missile_guidance(
+    mut missiles: Query<(&mut Missile, &mut Transform, &mut Velocity)>,
+    targets: Query<
+        &GlobalTransform,
+        (
+            With<Hitpoints>,
+            Or<(With<Asteroid>, With<Enemy>, With<BossPart>, With<Spaceship>)>,
+            Without<Missile>,
+        ),
+    >,
+    enemies: Query<(), Or<(With<Enemy>, With<BossPart>)>>,
+    index: Res<SpatialIndex>,
+    time: Res<SimulationTime>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut missile, mut transform, mut velocity) in missiles.iter_mut() {
+        let position = transform.translation;
+        let heading = velocity.0.normalize_or_zero();
+
+        let locked = missile.target.filter(|target| targets.get(*target).is_ok());
+        missile.target = locked.or_else(|| {
+            let source = missile.source;
+            let friendly = enemies.get(source).is_ok();
+
+            index
+                .query_circle(position.truncate(), missile.seek_range)
+                .into_iter()
+                .filter(|e| *e != source && !(friendly && enemies.get(*e).is_ok()))
+                .filter_map(|e| {
+                    let offset = (targets.get(e).ok()?.translation - position).truncate();
+                    let in_cone = offset.length() <= missile.seek_range
+                        && offset.angle_between(heading.truncate()).abs() <= missile.seek_angle;
+                    in_cone.then_some((e, offset.length_squared()))
+                })
+                .min_by(|(_, d1), (_, d2)| f32::total_cmp(d1, d2))
+                .map(|(e, _)| e)
+        });
+
+        let mut direction = heading;
+        if let Some(target) = missile.target.and_then(|target| targets.get(target).ok()) {
+            let wanted = (target.translation - position).truncate();
+            let turn = heading.truncate().angle_between(wanted);
+            let max_turn = missile.turn_rate * dt;
+            direction = Quat::from_rotation_z(turn.clamp(-max_turn, max_turn)) * heading;
+        }
+
+        velocity.0 = direction * missile.speed;
+        transform.rotation = Quat::from_rotation_z(Heading::angle_of(direction));
+    }
+}
+
+/// Blows up missiles on impact, damaging everything within the blast radius.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn missiles_hit(
+    mut cmd: Commands,
+    mut collisions: EventReader<CollisionStarted>,
+    missiles: Query<(&Missile, &Transform)>,
+    targets: Query<(&GlobalTransform, &Collider), With<Hitpoints>>,
+    enemies: Query<(), Or<(With<Enemy>, With<BossPart>)>>,
+    index: Res<SpatialIndex>,
+    sprites: Res<GameMaterials>,
+    mut damage: EventWriter<DamageEvent>,
+    mut exploded: Local<HashSet<Entity>>,
+) {
+    exploded.clear();
+
+    for collision in collisions.iter() {
+        let pair = collision.matching(|e| missiles.get(e).is_ok(), |e| targets.get(e).is_ok());
+        let (entity, target) = match pair {
+            Some(pair) => pair,
+            None => continue,
+        };
+
+        let (missile, transform) = missiles.get(entity).unwrap();
+        // missiles leave through the ship that launched them, enemies don't shoot each other down
+        let from_enemy = enemies.get(missile.source).is_ok();
+        let spared = |e: Entity| e == missile.source || (from_enemy && enemies.get(e).is_ok());
+        if spared(target) || !exploded.insert(entity) {
+            continue;
+        }
+
+        let blast = Shape::Circle {
+            center: transform.translation.truncate(),
+            radius: missile.blast_radius,
+        };
+        for hit in index.query_shape(&blast) {
+            let (hit_transform, collider) = match targets.get(hit) {
+                Ok(hit) => hit,
+                Err(_) => continue,
+            };
+            if spared(hit) || collider.shape(hit_transform).contact(&blast).is_none() {
+                continue;
+            }
+
+            damage.send(DamageEvent {
+                target: hit,
+                source: Some(missile.source),
+                amount: missile.damage,
+                kind: missile.kind,
+            });
+        }
+
+        cmd.entity(entity).despawn();
+        cmd.spawn_bundle(SpriteSheetBundle {
+            texture_atlas: sprites.laser_impact.clone(),
+            transform: Transform {
+                translation: transform.translation,
+                scale: Vec3::splat(missile.blast_radius / 4.),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(SpriteAnimation::new(75, 4))
+        .insert(Lifetime::millis(300))
+        .insert(CleanupAfterGame);
     }
 }
 
 pub fn laser_beam_init(
     mut commands: Commands,
-    added_laser_weapons: Query<Entity, Added<WeaponLaser>>,
+    added_laser_weapons: Query<(Entity, &WeaponLaser), Added<WeaponLaser>>,
+    definitions: Res<Assets<WeaponDefinition>>,
     sprites: Res<GameMaterials>,
 ) {
-    for entity in added_laser_weapons.iter() {
+    for (entity, laser) in added_laser_weapons.iter() {
+        let texture = definitions
+            .get(&laser.definition)
+            .map(|weapon| weapon.texture.clone())
+            .unwrap_or_default();
+
         commands
             .spawn_bundle(SpriteBundle {
-                texture: sprites.laser.clone(),
+                texture,
                 sprite: Sprite {
                     custom_size: Some(Vec2::new(2., 1.)),
                     anchor: Anchor::BottomCenter,
@@ -213,11 +837,17 @@ pub fn laser_beam_init(
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn laser_beam(
     mut cmd: Commands,
-    time: Res<Time>,
-    mut hitables: Query<(Entity, &GlobalTransform, &mut HitableByLaser)>,
-    mut weapons: Query<(&WeaponLaser, &GlobalTransform)>,
+    time: Res<SimulationTime>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    mut hitables: Query<
+        (Entity, &GlobalTransform, &Collider, &mut HitableByLaser),
+        Without<LaserBeam>,
+    >,
+    index: Res<SpatialIndex>,
+    mut weapons: Query<(&WeaponLaser, &WeaponSlot, &GlobalTransform)>,
     mut laser_beams: Query<(
         Entity,
         &mut LaserBeam,
@@ -225,6 +855,7 @@ pub fn laser_beam(
         &mut Visibility,
         &mut Transform,
     )>,
+    enemies: Query<(), Or<(With<Enemy>, With<BossPart>)>>,
     mut hit_this_frame: Local<HashSet<Entity>>,
 ) {
     use bevy::math::Vec3Swizzles as _;
@@ -232,7 +863,7 @@ pub fn laser_beam(
     hit_this_frame.clear();
 
     for (entity, mut laser_beam, sprite, mut visible, mut transform) in laser_beams.iter_mut() {
-        let (weapon, weapon_transform) = match weapons.get_mut(laser_beam.origin) {
+        let (weapon, weapon_slot, weapon_transform) = match weapons.get_mut(laser_beam.origin) {
             Ok(e) => e,
             _ => {
                 cmd.entity(entity).despawn_recursive();
@@ -240,30 +871,47 @@ pub fn laser_beam(
             }
         };
 
-        if let WeaponLaser::Firing(beam_dir) = weapon {
+        let (range, damage, damage_kind, damage_tick) = match definitions.get(&weapon.definition) {
+            Some(WeaponDefinition {
+                kind:
+                    WeaponKind::Beam {
+                        range,
+                        damage,
+                        damage_kind,
+                        damage_tick,
+                    },
+                ..
+            }) => (*range, *damage, *damage_kind, *damage_tick),
+            _ => continue,
+        };
+
+        if let Some(beam_dir) = weapon.firing {
             let beam_origin = weapon_transform.translation.xy();
 
-            let closest_hit = hitables
-                .iter_mut()
-                .filter_map(|(e, obstacle, hitable)| {
-                    math::ray_circle_intersection(
-                        beam_origin,
-                        beam_dir.xy(),
-                        obstacle.translation.xy(),
-                        16.,
-                    )
-                    .map(|hit| (hit, e, hitable))
+            let beam_end = beam_origin + beam_dir.xy() * range;
+            let candidates = index.query_segment(beam_origin, beam_end, 0.);
+            let from_enemy = enemies.get(weapon_slot.system).is_ok();
+
+            let closest_hit = candidates
+                .into_iter()
+                // the beam starts within the ship firing it, enemies don't shoot each other down
+                .filter(|e| *e != weapon_slot.system && !(from_enemy && enemies.get(*e).is_ok()))
+                .filter_map(|e| {
+                    let (_, obstacle, collider, _) = hitables.get(e).ok()?;
+                    collider
+                        .shape(obstacle)
+                        .raycast(beam_origin, beam_dir.xy())
+                        .filter(|hit| hit.distance_squared(beam_origin) <= range * range)
+                        .map(|hit| (hit, e))
                 })
-                .min_by(|(hit1, _, _), (hit2, _, _)| {
+                .min_by(|(hit1, _), (hit2, _)| {
                     f32::total_cmp(
                         &hit1.distance_squared(beam_origin),
                         &hit2.distance_squared(beam_origin),
                     )
                 });
 
-            let beam_end = closest_hit
-                .as_ref()
-                .map_or_else(|| beam_origin + (beam_dir.xy() * 1000.), |(hit, _, _)| *hit);
+            let beam_end = closest_hit.as_ref().map_or(beam_end, |(hit, _)| *hit);
 
             visible.is_visible = true;
             laser_beam.impacted = closest_hit.is_some();
@@ -278,7 +926,14 @@ pub fn laser_beam(
             transform.rotation =
                 Quat::from_rotation_z(beam_dir.angle_between(Vec3::Y) * -beam_dir.x.signum());
 
-            if let Some((_, entity, mut hitable)) = closest_hit {
+            if let Some((_, entity)) = closest_hit {
+                let (_, _, _, mut hitable) = hitables.get_mut(entity).unwrap();
+                hitable.damage = damage;
+                hitable.kind = damage_kind;
+                hitable.source = Some(weapon_slot.system);
+                hitable
+                    .damage_tick
+                    .set_duration(Duration::from_secs_f32(damage_tick));
                 hitable.damage_tick.tick(time.delta());
                 hit_this_frame.insert(entity);
             }
@@ -288,7 +943,7 @@ pub fn laser_beam(
         }
     }
 
-    for (e, _, mut hitable) in hitables.iter_mut() {
+    for (e, _, _, mut hitable) in hitables.iter_mut() {
         if !hit_this_frame.contains(&e) {
             hitable.damage_tick.reset();
         }
@@ -0,0 +1,113 @@
+use bevy::ecs::event::Events;
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+
+use game_test::asteroids::{spawn_asteroid, Asteroid, AsteroidArchetype, Shard};
+use game_test::basics::Velocity;
+use game_test::damage::DeathEvent;
+use game_test::simulation::headless_app;
+use game_test::Spaceship;
+
+/// Far away from the ship and everything flying around it.
+fn far_away() -> Vec3 {
+    Vec3::new(-4000., 3000., 0.)
+}
+
+fn started_game() -> App {
+    let mut app = headless_app();
+
+    let mut frames = 0;
+    while app
+        .world
+        .query::<&Spaceship>()
+        .iter(&app.world)
+        .next()
+        .is_none()
+    {
+        assert!(frames < 1000, "the game never started");
+        app.update();
+        frames += 1;
+    }
+
+    app
+}
+
+/// Spawns an asteroid of the archetype at `path` far away and destroys it.
+fn destroy(app: &mut App, path: &str) {
+    let handle: Handle<AsteroidArchetype> = app.world.resource::<AssetServer>().load(path);
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    let archetype = app
+        .world
+        .resource::<Assets<AsteroidArchetype>>()
+        .get(&handle);
+    let archetype = archetype.expect("the archetype is loaded");
+    let entity = spawn_asteroid(
+        &mut commands,
+        &handle,
+        archetype,
+        Transform::from_translation(far_away()),
+        Vec3::ZERO,
+        0.,
+        None,
+    );
+    queue.apply(&mut app.world);
+
+    app.update();
+    app.world
+        .resource_mut::<Events<DeathEvent>>()
+        .send(DeathEvent {
+            entity,
+            killer: None,
+        });
+    app.update();
+}
+
+/// Asteroids and shards near the destroyed one, by archetype path.
+fn debris(app: &mut App) -> (Vec<String>, Vec<String>) {
+    let world = &mut app.world;
+    let near = |transform: &Transform| transform.translation.distance(far_away()) < 200.;
+    let path = |world: &World, handle: &Handle<AsteroidArchetype>| {
+        let path = world.resource::<AssetServer>().get_handle_path(handle);
+        path.unwrap().path().to_string_lossy().into_owned()
+    };
+
+    let asteroids: Vec<_> = world
+        .query::<(&Asteroid, &Transform, Option<&Velocity>)>()
+        .iter(world)
+        .filter(|(_, transform, velocity)| near(transform) && velocity.is_some())
+        .map(|(asteroid, ..)| asteroid.archetype.clone())
+        .collect();
+    let shards: Vec<_> = world
+        .query::<(&Shard, &Transform)>()
+        .iter(world)
+        .filter(|(_, transform)| near(transform))
+        .map(|(shard, _)| shard.archetype.clone())
+        .collect();
+
+    (
+        asteroids.iter().map(|handle| path(world, handle)).collect(),
+        shards.iter().map(|handle| path(world, handle)).collect(),
+    )
+}
+
+#[test]
+fn splitting_asteroids_break_into_their_pieces() {
+    let mut app = started_game();
+    destroy(&mut app, "asteroids/boulder.asteroid.ron");
+
+    let (asteroids, shards) = debris(&mut app);
+    assert_eq!(asteroids, vec!["asteroids/rock.asteroid.ron"; 3]);
+    assert!(shards.is_empty());
+}
+
+#[test]
+fn asteroids_that_dont_split_drop_shards() {
+    let mut app = started_game();
+    destroy(&mut app, "asteroids/pebble.asteroid.ron");
+
+    let (asteroids, shards) = debris(&mut app);
+    assert!(asteroids.is_empty());
+    assert_eq!(shards, vec!["asteroids/pebble.asteroid.ron"; 2]);
+}
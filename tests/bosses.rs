@@ -0,0 +1,28 @@
+use game_test::bosses::Phase;
+
+fn phase(firing: Vec<usize>) -> Phase {
+    Phase {
+        destroyed: 0,
+        speed: 60.,
+        spin: 0.,
+        firing,
+    }
+}
+
+#[test]
+fn destroyed_parts_stop_firing() {
+    let phase = phase(vec![1, 2, 3]);
+    assert_eq!(phase.firing_parts(&[0, 1, 2, 3, 4]), vec![1, 2, 3]);
+    assert_eq!(phase.firing_parts(&[0, 2, 4]), vec![2]);
+}
+
+#[test]
+fn living_parts_take_over_for_destroyed_ones() {
+    let phase = phase(vec![0]);
+    assert_eq!(phase.firing_parts(&[2, 4]), vec![2, 4]);
+}
+
+#[test]
+fn nothing_fires_without_parts() {
+    assert!(phase(vec![0]).firing_parts(&[]).is_empty());
+}
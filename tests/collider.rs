@@ -0,0 +1,108 @@
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::prelude::*;
+
+use game_test::collider::{Collider, Shape};
+
+fn placed(collider: Collider, x: f32, y: f32, rotation: f32) -> Shape {
+    let transform = Transform::from_xyz(x, y, 0.).with_rotation(Quat::from_rotation_z(rotation));
+    collider.shape(&GlobalTransform::from(transform))
+}
+
+fn square(x: f32, y: f32, rotation: f32) -> Shape {
+    placed(Collider::Box(Vec2::splat(10.)), x, y, rotation)
+}
+
+fn origin() -> Shape {
+    square(0., 0., 0.)
+}
+
+#[test]
+fn boxes_touch_only_when_overlapping() {
+    assert!(origin().contact(&square(9., 0., 0.)).is_some());
+    assert!(origin().contact(&square(11., 0., 0.)).is_none());
+    assert!(origin().contact(&square(9., 9., 0.)).is_some());
+    assert!(origin().contact(&square(11., 9., 0.)).is_none());
+}
+
+#[test]
+fn rotated_boxes_use_their_own_axes() {
+    // the corner of the diamond reaches 5 * sqrt(2) ~ 7.07 to the side
+    assert!(origin().contact(&square(12., 0., FRAC_PI_4)).is_some());
+    assert!(origin().contact(&square(12.5, 0., FRAC_PI_4)).is_none());
+
+    // touching in the bounds, but the diamond's edge passes by the corner
+    assert!(origin().contact(&square(11., 11., FRAC_PI_4)).is_none());
+}
+
+#[test]
+fn box_follows_the_scale() {
+    let transform = Transform::from_xyz(14., 0., 0.).with_scale(Vec3::new(2., 1., 1.));
+    let wide = Collider::Box(Vec2::splat(10.)).shape(&GlobalTransform::from(transform));
+
+    assert!(origin().contact(&wide).is_some());
+    assert!(origin().contact(&square(14., 0., 0.)).is_none());
+}
+
+#[test]
+fn circles_touch_boxes_at_the_closest_point() {
+    let circle = placed(Collider::Circle(3.), 7., 0., 0.);
+    let contact = origin().contact(&circle).unwrap();
+    assert!(contact.abs_diff_eq(Vec2::new(5., 0.), 1e-5));
+
+    // near the corner the circle is out of reach even though the bounds overlap
+    let corner = placed(Collider::Circle(3.), 7.5, 7.5, 0.);
+    assert!(origin().contact(&corner).is_none());
+    assert!(corner.contact(&origin()).is_none());
+}
+
+#[test]
+fn separation_pushes_along_the_shallowest_axis() {
+    let offset = origin().separation(&square(8., 1., 0.)).unwrap();
+    assert!(offset.abs_diff_eq(Vec2::new(2., 0.), 1e-5));
+
+    let offset = origin().separation(&square(-1., -8., 0.)).unwrap();
+    assert!(offset.abs_diff_eq(Vec2::new(0., -2.), 1e-5));
+
+    assert!(origin().separation(&square(11., 0., 0.)).is_none());
+}
+
+#[test]
+fn separation_of_circles_pushes_away_from_the_center() {
+    let circle = placed(Collider::Circle(5.), 0., 0., 0.);
+    let other = placed(Collider::Circle(5.), 6., 8., 0.);
+    assert!(circle.separation(&other).is_none());
+
+    let other = placed(Collider::Circle(5.), 3., 4., 0.);
+    let offset = circle.separation(&other).unwrap();
+    assert!(offset.abs_diff_eq(Vec2::new(3., 4.), 1e-5));
+}
+
+#[test]
+fn separated_shapes_stop_touching() {
+    let shapes = [
+        origin(),
+        square(6., 3., 0.3),
+        placed(Collider::Circle(4.), -5., 2., 0.),
+    ];
+
+    for shape in &shapes {
+        for other in &shapes {
+            let offset = match shape.separation(other) {
+                Some(offset) if !std::ptr::eq(shape, other) => offset,
+                _ => continue,
+            };
+
+            let moved = match other {
+                Shape::Circle { center, radius } => Shape::Circle {
+                    center: *center + offset * 1.001,
+                    radius: *radius,
+                },
+                Shape::Polygon(points) => {
+                    Shape::Polygon(points.iter().map(|p| *p + offset * 1.001).collect())
+                }
+            };
+            assert!(shape.contact(&moved).is_none(), "{:?} {:?}", shape, moved);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::ecs::event::ManualEventReader;
+use bevy::prelude::*;
+
+use game_test::collider::Collider;
+use game_test::collision::{detect_collisions, CollisionEnded, CollisionStarted, Contacts};
+use game_test::spatial::{update_spatial_index, SpatialIndex};
+
+fn collision_app() -> App {
+    let mut app = App::new();
+    app.add_event::<CollisionStarted>()
+        .add_event::<CollisionEnded>()
+        .init_resource::<SpatialIndex>()
+        .init_resource::<Contacts>()
+        .add_system(update_spatial_index)
+        .add_system(detect_collisions.after(update_spatial_index));
+    app
+}
+
+fn circle(app: &mut App, x: f32) -> Entity {
+    app.world
+        .spawn()
+        .insert(GlobalTransform::from_xyz(x, 0., 0.))
+        .insert(Collider::Circle(10.))
+        .id()
+}
+
+/// Counts the started and ended events sent since the last call.
+struct Counter(
+    ManualEventReader<CollisionStarted>,
+    ManualEventReader<CollisionEnded>,
+);
+
+impl Counter {
+    fn new() -> Self {
+        Counter(default(), default())
+    }
+
+    fn count(&mut self, world: &World) -> (usize, usize) {
+        let started = self.0.iter(world.resource()).count();
+        let ended = self.1.iter(world.resource()).count();
+        (started, ended)
+    }
+}
+
+#[test]
+fn passing_by_starts_and_ends_one_contact() {
+    let mut app = collision_app();
+    circle(&mut app, 0.);
+    let moving = circle(&mut app, -102.);
+    let mut counter = Counter::new();
+
+    let (mut started, mut ended) = (0, 0);
+    for step in 0..=40 {
+        let x = -102. + step as f32 * 5.;
+        app.world
+            .entity_mut(moving)
+            .insert(GlobalTransform::from_xyz(x, 0., 0.));
+        app.update();
+
+        let (new_started, new_ended) = counter.count(&app.world);
+        started += new_started;
+        ended += new_ended;
+
+        // the circles overlap while less than 20 apart
+        let touching = x.abs() < 20.;
+        assert_eq!(started, (x > -20.) as usize, "at {}", x);
+        assert_eq!(ended, (x > 20.) as usize, "at {}", x);
+        assert_eq!(started - ended, touching as usize, "at {}", x);
+    }
+}
+
+#[test]
+fn despawning_ends_the_contact() {
+    let mut app = collision_app();
+    circle(&mut app, 0.);
+    let despawned = circle(&mut app, 5.);
+    let mut counter = Counter::new();
+
+    app.update();
+    app.update();
+    assert_eq!(counter.count(&app.world), (1, 0));
+
+    app.world.despawn(despawned);
+    app.update();
+    app.update();
+    assert_eq!(counter.count(&app.world), (0, 1));
+}
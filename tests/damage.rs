@@ -0,0 +1,76 @@
+use bevy::ecs::event::Events;
+use bevy::prelude::*;
+
+use game_test::basics::Hitpoints;
+use game_test::damage::{
+    apply_damage, DamageEvent, DamageKind, DeathEvent, HealEvent, Resistances,
+};
+
+fn damage_app() -> App {
+    let mut app = App::new();
+    app.add_event::<DamageEvent>()
+        .add_event::<HealEvent>()
+        .add_event::<DeathEvent>()
+        .add_system(apply_damage);
+    app
+}
+
+fn hit(app: &mut App, target: Entity, amount: u32, kind: DamageKind) -> u32 {
+    app.world
+        .resource_mut::<Events<DamageEvent>>()
+        .send(DamageEvent {
+            target,
+            source: None,
+            amount,
+            kind,
+        });
+    app.update();
+    app.world.get::<Hitpoints>(target).unwrap().current
+}
+
+#[test]
+fn resistances_scale_damage_of_their_kind() {
+    let mut app = damage_app();
+    let target = app
+        .world
+        .spawn()
+        .insert(Hitpoints::new(1000))
+        .insert(Resistances {
+            kinetic: 0.5,
+            energy: 0.25,
+            explosive: 1.,
+        })
+        .id();
+
+    assert_eq!(hit(&mut app, target, 100, DamageKind::Kinetic), 950);
+    assert_eq!(hit(&mut app, target, 100, DamageKind::Energy), 875);
+    assert_eq!(hit(&mut app, target, 100, DamageKind::Explosive), 875);
+}
+
+#[test]
+fn no_resistances_take_full_damage() {
+    let mut app = damage_app();
+    let target = app.world.spawn().insert(Hitpoints::new(1000)).id();
+
+    assert_eq!(hit(&mut app, target, 100, DamageKind::Kinetic), 900);
+    assert_eq!(hit(&mut app, target, 100, DamageKind::Energy), 800);
+    assert_eq!(hit(&mut app, target, 100, DamageKind::Explosive), 700);
+}
+
+#[test]
+fn resistances_stay_between_nothing_and_everything() {
+    let mut app = damage_app();
+    let target = app
+        .world
+        .spawn()
+        .insert(Hitpoints::new(1000))
+        .insert(Resistances {
+            kinetic: -1.,
+            energy: 2.,
+            explosive: 0.,
+        })
+        .id();
+
+    assert_eq!(hit(&mut app, target, 100, DamageKind::Kinetic), 900);
+    assert_eq!(hit(&mut app, target, 100, DamageKind::Energy), 900);
+}
@@ -0,0 +1,86 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use game_test::basics::{Hitpoints, Velocity};
+use game_test::controls::{Aim, PlayerInput};
+use game_test::replay::{play_replay, record_replay};
+use game_test::simulation::{
+    headless_app, SimulationLabel, SimulationRng, SimulationTime, SIMULATION_STAGE,
+};
+
+const TICKS: u64 = 900;
+
+/// Flies in circles while aiming around, firing in bursts and switching weapons now and then.
+fn scripted_input(time: Res<SimulationTime>, mut input: ResMut<PlayerInput>) {
+    let tick = time.tick;
+    let angle = tick as f32 / 240. * TAU;
+
+    *input = PlayerInput {
+        thrust: Vec2::new(angle.cos(), angle.sin()),
+        thrust_to_aim: false,
+        aim: Aim::Direction(Vec2::new(-angle.sin(), angle.cos())),
+        firing: tick % 120 < 60,
+        switch_weapon: (tick % 300 == 299) as i8,
+    };
+}
+
+#[derive(Debug, PartialEq)]
+struct Snapshot {
+    tick: u64,
+    rng: u64,
+    bodies: Vec<(Vec3, Quat, Vec3)>,
+    hitpoints: Vec<u32>,
+}
+
+fn run() -> Snapshot {
+    let mut app = headless_app();
+    app.add_system_to_stage(
+        SIMULATION_STAGE,
+        scripted_input
+            .after(play_replay)
+            .before(record_replay)
+            .before(SimulationLabel::Movement),
+    );
+
+    for _ in 0..TICKS * 4 {
+        app.update();
+        if app.world.resource::<SimulationTime>().tick >= TICKS {
+            break;
+        }
+    }
+
+    let world = &mut app.world;
+    let tick = world.resource::<SimulationTime>().tick;
+    assert_eq!(tick, TICKS, "the game didn't get far enough");
+
+    // the next number tells whether both runs drew the same amount of numbers
+    let rng = world.resource::<SimulationRng>().0.clone().gen();
+    let bodies = world
+        .query::<(&Transform, &Velocity)>()
+        .iter(world)
+        .map(|(transform, velocity)| (transform.translation, transform.rotation, velocity.0))
+        .collect();
+    let hitpoints = world
+        .query::<&Hitpoints>()
+        .iter(world)
+        .map(|hp| hp.current)
+        .collect();
+
+    Snapshot {
+        tick,
+        rng,
+        bodies,
+        hitpoints,
+    }
+}
+
+#[test]
+fn same_input_gives_same_state() {
+    let first = run();
+    let second = run();
+
+    assert!(!first.bodies.is_empty());
+    assert_eq!(first, second);
+}
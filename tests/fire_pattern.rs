@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use game_test::fire_pattern::{Charge, FirePattern, Trigger, TriggerInput};
+
+const DT: f32 = 0.1;
+
+const HELD: TriggerInput = TriggerInput {
+    pulled: true,
+    selected: true,
+    auto_release: false,
+};
+
+const RELEASED: TriggerInput = TriggerInput {
+    pulled: false,
+    selected: true,
+    auto_release: false,
+};
+
+fn angle(dir: Vec3) -> f32 {
+    dir.y.atan2(dir.x)
+}
+
+/// Runs the trigger for a tick each input, returns the multiplier of each tick.
+fn fire(pattern: &FirePattern, cooldown: f32, inputs: &[TriggerInput]) -> Vec<Option<f32>> {
+    let mut trigger = Trigger::default();
+    let mut cooldown = Timer::from_seconds(cooldown, false);
+    cooldown.tick(Duration::from_secs_f32(cooldown.duration().as_secs_f32()));
+
+    inputs
+        .iter()
+        .map(|input| {
+            let shot = trigger.update(pattern, *input, &mut cooldown, DT);
+            cooldown.tick(Duration::from_secs_f32(DT));
+            shot
+        })
+        .collect()
+}
+
+fn charged(time: f32, max_multiplier: f32) -> FirePattern {
+    FirePattern {
+        charge: Some(Charge {
+            time,
+            max_multiplier,
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn single_projectile_goes_along_the_aim() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let dirs = FirePattern::default().directions(Vec3::X, &mut rng);
+    assert_eq!(dirs, vec![Vec3::X]);
+}
+
+#[test]
+fn spread_fans_out_evenly_around_the_aim() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let pattern = FirePattern {
+        count: 3,
+        spread: 1.,
+        ..Default::default()
+    };
+
+    let angles: Vec<f32> = pattern
+        .directions(Vec3::X, &mut rng)
+        .into_iter()
+        .map(angle)
+        .collect();
+
+    assert_eq!(angles.len(), 3);
+    for (angle, expected) in angles.iter().zip([-0.5, 0., 0.5]) {
+        assert!((angle - expected).abs() < 1e-5, "{:?}", angles);
+    }
+}
+
+#[test]
+fn inaccuracy_stays_within_bounds() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let pattern = FirePattern {
+        inaccuracy: 0.2,
+        ..Default::default()
+    };
+
+    for _ in 0..100 {
+        let dir = pattern.directions(Vec3::X, &mut rng)[0];
+        assert!(angle(dir).abs() <= 0.2);
+        assert!((dir.length() - 1.).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn fires_again_once_the_cooldown_is_over() {
+    let shots = fire(&FirePattern::default(), 0.25, &[HELD; 6]);
+    assert_eq!(shots, vec![Some(1.), None, None, Some(1.), None, None]);
+}
+
+#[test]
+fn burst_finishes_after_letting_go() {
+    let pattern = FirePattern {
+        burst: 3,
+        burst_delay: DT,
+        ..Default::default()
+    };
+
+    let shots = fire(&pattern, 1., &[HELD, RELEASED, RELEASED, RELEASED]);
+    assert_eq!(shots, vec![Some(1.), Some(1.), Some(1.), None]);
+}
+
+#[test]
+fn charge_fires_only_on_release() {
+    let pattern = charged(0.4, 3.);
+
+    let shots = fire(
+        &pattern,
+        0.,
+        &[HELD, HELD, HELD, HELD, HELD, HELD, RELEASED],
+    );
+    assert_eq!(shots[..6], [None; 6]);
+    assert_eq!(shots[6], Some(3.));
+}
+
+#[test]
+fn short_charge_scales_the_multiplier() {
+    let pattern = charged(0.4, 3.);
+
+    let shots = fire(&pattern, 0., &[HELD, HELD, RELEASED]);
+    let multiplier = shots[2].unwrap();
+    assert!((multiplier - 2.).abs() < 1e-5, "{}", multiplier);
+}
+
+#[test]
+fn switching_away_drops_the_charge() {
+    let pattern = charged(0.4, 3.);
+    let switched = TriggerInput {
+        selected: false,
+        ..HELD
+    };
+
+    let shots = fire(&pattern, 0., &[HELD, HELD, switched, RELEASED]);
+    assert_eq!(shots, vec![None; 4]);
+}
+
+#[test]
+fn auto_release_fires_when_fully_charged() {
+    let pattern = charged(0.2, 3.);
+    let auto = TriggerInput {
+        auto_release: true,
+        ..HELD
+    };
+
+    let shots = fire(&pattern, 0., &[auto; 5]);
+    assert_eq!(shots, vec![None, Some(3.), None, Some(3.), None]);
+}
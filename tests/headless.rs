@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+use game_test::simulation::headless_app;
+use game_test::Spaceship;
+
+fn has_ship(app: &mut App) -> bool {
+    let world = &mut app.world;
+    world.query::<&Spaceship>().iter(world).next().is_some()
+}
+
+#[test]
+fn plays_without_a_window() {
+    let mut app = headless_app();
+
+    // the game starts within the first few frames
+    let mut frames = 0;
+    while !has_ship(&mut app) {
+        assert!(frames < 1000, "the game never started");
+        app.update();
+        frames += 1;
+    }
+
+    for _ in 0..600 {
+        app.update();
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::BTreeSet;
+
+use bevy::ecs::event::Events;
+use bevy::prelude::*;
+
+use game_test::asteroids::Asteroid;
+use game_test::basics::{Hitpoints, Velocity};
+use game_test::damage::DeathEvent;
+use game_test::level_generation::{Chunk, ChunkExplorer, FieldAsteroid, LevelGenerator};
+use game_test::simulation::headless_app;
+use game_test::Spaceship;
+
+fn started_game() -> App {
+    let mut app = headless_app();
+    // a world with fields around the start
+    app.insert_resource(LevelGenerator::new(1));
+
+    let mut frames = 0;
+    while app
+        .world
+        .query::<&Spaceship>()
+        .iter(&app.world)
+        .next()
+        .is_none()
+    {
+        assert!(frames < 1000, "the game never started");
+        app.update();
+        frames += 1;
+    }
+
+    app
+}
+
+fn field_asteroids(app: &mut App) -> BTreeSet<(Chunk, FieldAsteroid)> {
+    let world = &mut app.world;
+    world
+        .query_filtered::<(&Chunk, &FieldAsteroid), (With<Asteroid>, With<Hitpoints>)>()
+        .iter(world)
+        .map(|(chunk, field)| (chunk.clone(), *field))
+        .collect()
+}
+
+fn fly_to(app: &mut App, position: Vec3) {
+    let world = &mut app.world;
+    let mut ships = world.query_filtered::<(&mut Transform, &mut Velocity), With<ChunkExplorer>>();
+    for (mut transform, mut velocity) in ships.iter_mut(world) {
+        transform.translation = position;
+        velocity.0 = Vec3::ZERO;
+    }
+
+    // long enough for the chunks left behind to get cleaned up
+    for _ in 0..120 {
+        app.update();
+    }
+}
+
+#[test]
+fn destroyed_field_asteroids_stay_gone() {
+    let mut app = started_game();
+    app.update();
+    let generated = field_asteroids(&mut app);
+    assert!(generated.len() >= 2, "no fields around the start");
+
+    let world = &mut app.world;
+    let destroyed: Vec<(Entity, Chunk, FieldAsteroid)> = world
+        .query_filtered::<(Entity, &Chunk, &FieldAsteroid), With<Hitpoints>>()
+        .iter(world)
+        .take(2)
+        .map(|(entity, chunk, field)| (entity, chunk.clone(), *field))
+        .collect();
+    for (entity, ..) in &destroyed {
+        world.resource_mut::<Events<DeathEvent>>().send(DeathEvent {
+            entity: *entity,
+            killer: None,
+        });
+    }
+    app.update();
+
+    fly_to(&mut app, Vec3::new(30000., 0., 0.));
+    assert!(field_asteroids(&mut app).is_disjoint(&generated));
+
+    fly_to(&mut app, Vec3::ZERO);
+    let gone: BTreeSet<_> = app
+        .world
+        .resource::<LevelGenerator>()
+        .destroyed_asteroids()
+        .into_iter()
+        .collect();
+    let regenerated: BTreeSet<_> = field_asteroids(&mut app)
+        .intersection(&generated)
+        .cloned()
+        .collect();
+    for (_, chunk, field) in destroyed {
+        assert!(gone.contains(&(chunk.clone(), field)));
+        assert!(!regenerated.contains(&(chunk, field)));
+    }
+
+    let expected: BTreeSet<_> = generated.difference(&gone).cloned().collect();
+    assert_eq!(regenerated, expected);
+}
@@ -0,0 +1,175 @@
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+
+use game_test::asteroids::{spawn_asteroid, AsteroidArchetype};
+use game_test::basics::{Hitpoints, Lifetime};
+use game_test::collider::Collider;
+use game_test::enemies::Enemy;
+use game_test::level_generation::ChunkExplorer;
+use game_test::simulation::headless_app;
+use game_test::weapons::{spawn_missile, Missile, WeaponDefinition};
+use game_test::Spaceship;
+
+/// Far away from the ship and everything flying around it.
+fn far_away() -> Vec3 {
+    Vec3::new(-4000., 3000., 0.)
+}
+
+fn started_game() -> App {
+    let mut app = headless_app();
+
+    let mut frames = 0;
+    while app
+        .world
+        .query::<&Spaceship>()
+        .iter(&app.world)
+        .next()
+        .is_none()
+    {
+        assert!(frames < 1000, "the game never started");
+        app.update();
+        frames += 1;
+    }
+
+    app
+}
+
+fn player_ship(app: &mut App) -> Entity {
+    let world = &mut app.world;
+    let mut ships = world.query_filtered::<Entity, With<ChunkExplorer>>();
+    ships.iter(world).next().unwrap()
+}
+
+fn boulder(app: &mut App, position: Vec3) -> Entity {
+    let handle: Handle<AsteroidArchetype> = app
+        .world
+        .resource::<AssetServer>()
+        .load("asteroids/boulder.asteroid.ron");
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    let archetype = app
+        .world
+        .resource::<Assets<AsteroidArchetype>>()
+        .get(&handle);
+    let entity = spawn_asteroid(
+        &mut commands,
+        &handle,
+        archetype.expect("the archetype is loaded"),
+        Transform::from_translation(position),
+        Vec3::ZERO,
+        0.,
+        None,
+    );
+    queue.apply(&mut app.world);
+    entity
+}
+
+/// Enemy ship standing still, without an archetype to steer it.
+fn enemy(app: &mut App, position: Vec3) -> Entity {
+    app.world
+        .spawn()
+        .insert(Transform::from_translation(position))
+        .insert(GlobalTransform::from_translation(position))
+        .insert(Enemy {
+            archetype: default(),
+            strafe: 1.,
+        })
+        .insert(Hitpoints::new(100))
+        .insert(Collider::Circle(20.))
+        .id()
+}
+
+fn launch(app: &mut App, source: Entity, position: Vec3, direction: Vec3) {
+    let handle: Handle<WeaponDefinition> = app
+        .world
+        .resource::<AssetServer>()
+        .load("weapons/missile.weapon.ron");
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    let definitions = app.world.resource::<Assets<WeaponDefinition>>();
+    let definition = definitions.get(&handle).expect("the launcher is loaded");
+    let missile = Missile::new(&handle, definition, source).unwrap();
+    spawn_missile(
+        &mut commands,
+        definition,
+        missile,
+        Transform::from_translation(position),
+        direction * 500.,
+        Lifetime::seconds(3),
+    );
+    queue.apply(&mut app.world);
+}
+
+fn hitpoints(app: &App, entity: Entity) -> u32 {
+    app.world
+        .get::<Hitpoints>(entity)
+        .map_or(0, |hp| hp.current)
+}
+
+fn run(app: &mut App, ticks: usize) {
+    for _ in 0..ticks {
+        app.update();
+    }
+}
+
+#[test]
+fn missiles_turn_towards_targets_in_their_cone() {
+    let mut app = started_game();
+    let source = player_ship(&mut app);
+    // off to the side, flying straight ahead would pass it by
+    let target = boulder(&mut app, far_away() + Vec3::new(200., 300., 0.));
+    launch(&mut app, source, far_away(), Vec3::Y);
+
+    run(&mut app, 60);
+    assert!(hitpoints(&app, target) < 12);
+}
+
+#[test]
+fn missiles_ignore_targets_outside_of_their_cone() {
+    let mut app = started_game();
+    let source = player_ship(&mut app);
+    let behind = boulder(&mut app, far_away() + Vec3::new(200., -300., 0.));
+    launch(&mut app, source, far_away(), Vec3::Y);
+
+    run(&mut app, 60);
+    assert_eq!(hitpoints(&app, behind), 12);
+}
+
+#[test]
+fn enemy_blasts_spare_other_enemies() {
+    let mut app = started_game();
+    let source = enemy(&mut app, far_away() + Vec3::new(0., -300., 0.));
+    let target = boulder(&mut app, far_away());
+    let wingman = enemy(&mut app, far_away() + Vec3::new(60., 0., 0.));
+    launch(
+        &mut app,
+        source,
+        far_away() - Vec3::new(0., 50., 0.),
+        Vec3::Y,
+    );
+
+    run(&mut app, 30);
+    assert!(hitpoints(&app, target) < 12);
+    assert_eq!(hitpoints(&app, wingman), 100);
+    assert_eq!(hitpoints(&app, source), 100);
+}
+
+#[test]
+fn player_blasts_hit_enemies_nearby() {
+    let mut app = started_game();
+    let source = player_ship(&mut app);
+    let target = boulder(&mut app, far_away());
+    let bystander = enemy(&mut app, far_away() + Vec3::new(60., 0., 0.));
+    launch(
+        &mut app,
+        source,
+        far_away() - Vec3::new(0., 50., 0.),
+        Vec3::Y,
+    );
+
+    run(&mut app, 30);
+    assert!(hitpoints(&app, target) < 12);
+    assert!(hitpoints(&app, bystander) < 100);
+}
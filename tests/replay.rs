@@ -0,0 +1,98 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use game_test::basics::{Hitpoints, Velocity};
+use game_test::controls::{Aim, PlayerInput};
+use game_test::level_generation::LevelGenerator;
+use game_test::replay::{play_replay, record_replay, Replay, ReplayPlayer, ReplayRecorder};
+use game_test::simulation::{
+    headless_app, SimulationLabel, SimulationRng, SimulationTime, SIMULATION_STAGE,
+};
+
+const TICKS: u64 = 600;
+
+/// Flies a figure eight, firing now and then and switching weapons once.
+fn scripted_input(time: Res<SimulationTime>, mut input: ResMut<PlayerInput>) {
+    let tick = time.tick;
+    let angle = tick as f32 / 300. * TAU;
+
+    *input = PlayerInput {
+        thrust: Vec2::new(angle.sin(), (2. * angle).sin()),
+        thrust_to_aim: tick % 200 < 50,
+        aim: Aim::Position(Vec3::new(angle.cos(), angle.sin(), 0.) * 400.),
+        firing: tick % 90 < 40,
+        switch_weapon: (tick == 300) as i8,
+    };
+}
+
+#[derive(Debug, PartialEq)]
+struct Snapshot {
+    tick: u64,
+    rng: u64,
+    bodies: Vec<(Vec3, Quat, Vec3)>,
+    hitpoints: Vec<u32>,
+}
+
+fn run_until(app: &mut App, tick: u64) -> Snapshot {
+    for _ in 0..tick * 4 {
+        if app.world.resource::<SimulationTime>().tick >= tick {
+            break;
+        }
+        app.update();
+    }
+
+    let world = &mut app.world;
+    let rng = world.resource::<SimulationRng>().0.clone().gen();
+    let bodies = world
+        .query::<(&Transform, &Velocity)>()
+        .iter(world)
+        .map(|(transform, velocity)| (transform.translation, transform.rotation, velocity.0))
+        .collect();
+    let hitpoints = world
+        .query::<&Hitpoints>()
+        .iter(world)
+        .map(|hp| hp.current)
+        .collect();
+
+    Snapshot {
+        tick: world.resource::<SimulationTime>().tick,
+        rng,
+        bodies,
+        hitpoints,
+    }
+}
+
+#[test]
+fn played_replay_ends_in_the_recorded_state() {
+    let path = std::env::temp_dir().join(format!("asteroids-{}.replay", std::process::id()));
+
+    let mut recording = headless_app();
+    recording
+        .insert_resource(LevelGenerator::new(42))
+        .insert_resource(ReplayRecorder::new(&path))
+        .add_system_to_stage(
+            SIMULATION_STAGE,
+            scripted_input
+                .after(play_replay)
+                .before(record_replay)
+                .before(SimulationLabel::Movement),
+        );
+    let recorded = run_until(&mut recording, TICKS);
+    recording.world.resource_mut::<ReplayRecorder>().finish();
+
+    let replay = Replay::load(&path).expect("the recorded replay loads");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replay.seed, 42);
+
+    let mut playing = headless_app();
+    playing
+        .insert_resource(LevelGenerator::new(replay.seed))
+        .insert_resource(ReplayPlayer::new(replay));
+    let played = run_until(&mut playing, TICKS);
+
+    assert_eq!(recorded.tick, TICKS, "the game didn't get far enough");
+    assert!(!recorded.bodies.is_empty());
+    assert_eq!(recorded, played);
+}
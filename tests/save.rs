@@ -0,0 +1,110 @@
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+
+use game_test::basics::{Hitpoints, Velocity};
+use game_test::bosses::BossPart;
+use game_test::director::Director;
+use game_test::enemies::Enemy;
+use game_test::save::{ResumeRun, RunSnapshot, SavePlugin, SavedRun};
+use game_test::simulation::{headless_app, SimulationTime};
+use game_test::weapons::{Bullet, Missile};
+use game_test::Spaceship;
+
+fn started_game() -> App {
+    let mut app = headless_app();
+    app.add_plugin(SavePlugin);
+
+    let mut frames = 0;
+    while app
+        .world
+        .query::<&Spaceship>()
+        .iter(&app.world)
+        .next()
+        .is_none()
+    {
+        assert!(frames < 1000, "the game never started");
+        app.update();
+        frames += 1;
+    }
+
+    app
+}
+
+#[derive(Debug, PartialEq)]
+struct Fight {
+    tick: u64,
+    enemies: Vec<(Vec3, Vec3, u32)>,
+    parts: Vec<(usize, u32)>,
+    bullets: Vec<(Vec3, u32)>,
+    missiles: Vec<(Vec3, u32)>,
+}
+
+fn fight(world: &mut World) -> Fight {
+    let mut enemies: Vec<_> = world
+        .query_filtered::<(&Transform, &Velocity, &Hitpoints), With<Enemy>>()
+        .iter(world)
+        .map(|(transform, velocity, hp)| (transform.translation, velocity.0, hp.current))
+        .collect();
+    let mut parts: Vec<_> = world
+        .query::<(&BossPart, &Hitpoints)>()
+        .iter(world)
+        .map(|(part, hp)| (part.index, hp.current))
+        .collect();
+    let mut bullets: Vec<_> = world
+        .query::<(&Bullet, &Transform)>()
+        .iter(world)
+        .map(|(bullet, transform)| (transform.translation, bullet.damage))
+        .collect();
+    let mut missiles: Vec<_> = world
+        .query::<(&Missile, &Transform)>()
+        .iter(world)
+        .map(|(missile, transform)| (transform.translation, missile.damage))
+        .collect();
+
+    let by_position = |a: &(Vec3, _, _), b: &(Vec3, _, _)| a.0.x.total_cmp(&b.0.x);
+    enemies.sort_by(by_position);
+    parts.sort();
+    bullets.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+    missiles.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+
+    Fight {
+        tick: world.resource::<SimulationTime>().tick,
+        enemies,
+        parts,
+        bullets,
+        missiles,
+    }
+}
+
+#[test]
+fn continued_run_keeps_the_fight_going() {
+    let mut app = started_game();
+    {
+        let mut director = app.world.resource_mut::<Director>();
+        director.wave = 5;
+        director.enemies = 3;
+        director.boss = true;
+    }
+    for _ in 0..400 {
+        app.update();
+    }
+
+    let saved = fight(&mut app.world);
+    assert!(saved.enemies.len() >= 3);
+    assert!(!saved.parts.is_empty());
+    assert!(!saved.bullets.is_empty() || !saved.missiles.is_empty());
+
+    let mut snapshot = SystemState::<RunSnapshot>::new(&mut app.world);
+    let run = snapshot
+        .get_mut(&mut app.world)
+        .take()
+        .expect("the ship is still flying");
+    let run: SavedRun = ron::from_str(&ron::to_string(&run).unwrap()).unwrap();
+
+    let mut continued = started_game();
+    continued.world.resource_mut::<SimulationTime>().paused = true;
+    continued.insert_resource(ResumeRun(run));
+    continued.update();
+
+    assert_eq!(saved, fight(&mut continued.world));
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use game_test::collider::{Collider, Shape};
+use game_test::spatial::{update_spatial_index, SpatialIndex};
+
+/// Scatters colliders of all kinds and sizes, some spanning several cells.
+fn scattered(app: &mut App, rng: &mut ChaCha8Rng) -> Vec<(Entity, Shape)> {
+    (0..300)
+        .map(|_| {
+            let size = rng.gen_range(2. ..300.);
+            let collider = match rng.gen_range(0..3) {
+                0 => Collider::Circle(size / 2.),
+                1 => Collider::Box(Vec2::new(size, rng.gen_range(2. ..300.))),
+                _ => Collider::Polygon(vec![
+                    Vec2::new(-size, -size / 2.),
+                    Vec2::new(size, 0.),
+                    Vec2::new(0., size),
+                ]),
+            };
+            let transform = Transform::from_xyz(
+                rng.gen_range(-1500. ..1500.),
+                rng.gen_range(-1500. ..1500.),
+                0.,
+            )
+            .with_rotation(Quat::from_rotation_z(rng.gen_range(0. ..6.3)));
+            let transform = GlobalTransform::from(transform);
+
+            let shape = collider.shape(&transform);
+            let entity = app.world.spawn().insert(transform).insert(collider).id();
+            (entity, shape)
+        })
+        .collect()
+}
+
+fn overlapping(a: (Vec2, Vec2), b: (Vec2, Vec2)) -> bool {
+    a.0.x <= b.1.x && a.1.x >= b.0.x && a.0.y <= b.1.y && a.1.y >= b.0.y
+}
+
+#[test]
+fn index_finds_what_checking_everything_finds() {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let mut app = App::new();
+    app.init_resource::<SpatialIndex>()
+        .add_system(update_spatial_index);
+    let colliders = scattered(&mut app, &mut rng);
+    app.update();
+    let index = app.world.resource::<SpatialIndex>();
+
+    let brute_force = |area: (Vec2, Vec2)| -> Vec<Entity> {
+        let mut found: Vec<Entity> = colliders
+            .iter()
+            .filter(|(_, shape)| overlapping(shape.bounds(), area))
+            .map(|(entity, _)| *entity)
+            .collect();
+        found.sort_unstable();
+        found
+    };
+
+    for (_, shape) in &colliders {
+        assert_eq!(index.query_shape(shape), brute_force(shape.bounds()));
+    }
+
+    for _ in 0..200 {
+        let center = Vec2::new(rng.gen_range(-1600. ..1600.), rng.gen_range(-1600. ..1600.));
+        let radius = rng.gen_range(1. ..400.);
+        assert_eq!(
+            index.query_circle(center, radius),
+            brute_force((center - radius, center + radius)),
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use game_test::config::{WaveConfig, WavePoint};
+
+fn point(wave: u32, count: f32) -> WavePoint {
+    WavePoint {
+        wave,
+        count,
+        speed: 1.,
+        interval: 2.,
+        enemies: 0.,
+    }
+}
+
+fn config() -> WaveConfig {
+    WaveConfig {
+        curve: vec![point(1, 4.), point(5, 12.), point(10, 22.)],
+        waves_per_minute: 0.,
+        waves_per_score: 0.,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn hits_the_points_of_the_curve() {
+    let config = config();
+    assert_eq!(config.at(1, 0., 0).count, 4.);
+    assert_eq!(config.at(5, 0., 0).count, 12.);
+    assert_eq!(config.at(10, 0., 0).count, 22.);
+}
+
+#[test]
+fn interpolates_between_points() {
+    let config = config();
+    assert_eq!(config.at(3, 0., 0).count, 8.);
+    assert_eq!(config.at(7, 0., 0).count, 16.);
+}
+
+#[test]
+fn clamps_outside_of_the_curve() {
+    let config = config();
+    assert_eq!(config.at(0, 0., 0).count, 4.);
+    assert_eq!(config.at(50, 0., 0).count, 22.);
+}
+
+#[test]
+fn time_and_score_push_the_difficulty_ahead() {
+    let config = WaveConfig {
+        waves_per_minute: 0.5,
+        waves_per_score: 0.01,
+        ..config()
+    };
+
+    // four minutes in, two waves ahead
+    assert_eq!(config.at(1, 240., 0).count, 8.);
+    // 200 points, two waves ahead
+    assert_eq!(config.at(1, 0., 200).count, 8.);
+    assert_eq!(config.at(1, 240., 200).count, 12.);
+
+    // the wave number itself stays
+    assert_eq!(config.at(1, 240., 200).wave, 1);
+}
+
+#[test]
+fn empty_curve_gives_the_default() {
+    let config = WaveConfig {
+        curve: Vec::new(),
+        ..config()
+    };
+    assert_eq!(config.at(3, 0., 0).count, WavePoint::default().count);
+}